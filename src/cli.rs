@@ -0,0 +1,131 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use bach::config::Config;
+use bach::gen::{Format, Theme};
+
+// Command line arguments for the bach binary
+#[derive(Debug, Parser)]
+#[command(name = "bach", version, about = "A documentation generator for Java")]
+pub struct Args {
+    /// Directories to scan for documented source files [default: ./]
+    #[arg(value_name = "ROOT")]
+    pub roots: Vec<PathBuf>,
+
+    /// Config file to use instead of looking for bach.toml in the first root
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Directory to write the generated documentation into [default: ./bach]
+    #[arg(short, long, value_name = "DIR")]
+    pub out: Option<PathBuf>,
+
+    /// Theme used for the generated pages: a built-in name, a CSS file or a theme directory [default: default]
+    #[arg(short, long, value_name = "THEME", value_parser = parse_theme)]
+    pub theme: Option<Theme>,
+
+    /// Output format, can be given more than once: html, json or markdown [default: html]
+    #[arg(short, long = "format", value_name = "FORMAT", value_parser = parse_format)]
+    pub formats: Vec<Format>,
+
+    /// Title of the generated documentation [default: the name of the first root directory]
+    #[arg(long, value_name = "TITLE")]
+    pub title: Option<String>,
+
+    /// Report how much of the public API is documented instead of writing documentation
+    #[arg(long)]
+    pub coverage: bool,
+
+    /// With --coverage, fail if less than this percentage is documented
+    #[arg(long, value_name = "PERCENT", requires = "coverage")]
+    pub threshold: Option<f64>,
+
+    /// With --coverage, where to write the JSON report [default: <DIR>/coverage.json]
+    #[arg(long, value_name = "FILE", requires = "coverage")]
+    pub report: Option<PathBuf>,
+
+    /// Treat every warning as an error, so nothing is written if there are any
+    #[arg(long)]
+    pub deny_warnings: bool,
+
+    /// How to print warnings and errors about the source: human-readable on stderr, or one JSON
+    /// object per line on stdout with nothing else printed
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+
+    /// Only print errors
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print every file as it is scanned
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+impl Args {
+    // Override the settings from bach.toml with anything given on the command line
+    pub fn apply(&self, config: &mut Config) {
+        if !self.roots.is_empty() {
+            config.roots = self.roots.clone();
+        }
+        if self.out.is_some() {
+            config.out = self.out.clone();
+        }
+        if self.theme.is_some() {
+            config.theme = self.theme.clone();
+        }
+        if !self.formats.is_empty() {
+            config.formats = self.formats.clone();
+        }
+        if self.threshold.is_some() {
+            config.coverage.threshold = self.threshold;
+        }
+        if self.report.is_some() {
+            config.coverage.report = self.report.clone();
+        }
+        if self.title.is_some() {
+            config.title = self.title.clone();
+        }
+    }
+
+    // How chatty logb! should be: 0 is quiet, 1 is normal and 2 is verbose
+    // JSON diagnostics go to stdout, so they're kept apart from the log by being quiet
+    pub fn verbosity(&self) -> u8 {
+        if self.quiet || self.message_format == MessageFormat::Json {
+            0
+        } else if self.verbose {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+// How diagnostics are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+// Turn a --theme value into a Theme, listing the valid names when it's neither one of them nor a path
+fn parse_theme(s: &str) -> Result<Theme, String> {
+    match Theme::parse(s) {
+        Theme::Custom(p) if !p.exists() => {
+            Err(format!("unknown theme and no such file or directory (expected a path or one of: {})",
+                        Theme::ALL.iter().map(|t| t.name()).collect::<Vec<&str>>().join(", ")))
+        },
+        t => Ok(t),
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, String> {
+    Format::parse(s).ok_or_else(|| String::from("unknown format (expected html, json or markdown)"))
+}
@@ -0,0 +1,174 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::diagnostic::line_col;
+use crate::gen::{Format, Theme};
+use crate::lint;
+
+// Name of the config file looked for in the scan root
+pub static CONFIG_FILE: &str = "bach.toml";
+
+// Per-project settings read from bach.toml
+// Every key is optional, and anything given on the command line wins over it
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    // Title of the generated documentation
+    pub title: Option<String>,
+    // Directories to scan, relative to the config file
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+    // Globs a file must match to be scanned
+    #[serde(default)]
+    pub include: Vec<String>,
+    // Globs of files that are never scanned, instead of scan::DEFAULT_EXCLUDE
+    pub exclude: Option<Vec<String>>,
+    // Where to write the output, relative to the config file
+    pub out: Option<PathBuf>,
+    pub theme: Option<Theme>,
+    // URL or path of the image at the top of the sidebar
+    pub logo: Option<String>,
+    #[serde(default)]
+    pub formats: Vec<Format>,
+    // Severity of each lint, by name
+    #[serde(default)]
+    pub lints: BTreeMap<String, Level>,
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+}
+
+// Just the names under `[lints]` and where they are, to point at any that aren't lints
+#[derive(Deserialize)]
+struct LintNames {
+    #[serde(default)]
+    lints: BTreeMap<toml::Spanned<String>, toml::Value>,
+}
+
+// The `[coverage]` table, for `bach --coverage`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoverageConfig {
+    // The lowest percentage of the public API that has to be documented
+    pub threshold: Option<f64>,
+    // Where to write the JSON report, relative to the config file
+    pub report: Option<PathBuf>,
+}
+
+// How seriously to take a lint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allow"),
+            Level::Warn => write!(f, "warn"),
+            Level::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+impl Config {
+    // Look for bach.toml in `dir`, returning None if there isn't one
+    pub fn discover(dir: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = dir.join(CONFIG_FILE);
+        if path.is_file() {
+            Config::load(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Read and parse a config file
+    // Relative paths inside it are made relative to the directory it lives in
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let src = fs::read_to_string(path).map_err(|e| ConfigError {
+            path: path.to_owned(),
+            pos: None,
+            message: e.to_string(),
+        })?;
+
+        let mut config: Config = toml::from_str(&src).map_err(|e| ConfigError {
+            path: path.to_owned(),
+            pos: e.span().map(|s| line_col(&src, s.start)),
+            message: e.message().to_owned(),
+        })?;
+
+        // The first name that isn't a lint, in the order they're written
+        let names: LintNames = toml::from_str(&src).map_err(|e| ConfigError {
+            path: path.to_owned(),
+            pos: e.span().map(|s| line_col(&src, s.start)),
+            message: e.message().to_owned(),
+        })?;
+        if let Some(name) = names.lints.keys().filter(|n| !lint::is_lint(n.get_ref())).min_by_key(|n| n.span().start) {
+            return Err(ConfigError {
+                path: path.to_owned(),
+                pos: Some(line_col(&src, name.span().start)),
+                message: lint::unknown(name.get_ref()),
+            });
+        }
+
+        let base = path.parent().unwrap_or_else(|| Path::new("./"));
+        config.roots = config.roots.iter().map(|r| base.join(r)).collect();
+        config.out = config.out.map(|o| base.join(o));
+        config.coverage.report = config.coverage.report.map(|r| base.join(r));
+        config.theme = config.theme.map(|t| match t {
+            Theme::Custom(p) => Theme::Custom(base.join(p)),
+            t => t,
+        });
+
+        Ok(config)
+    }
+}
+
+// Something wrong with a config file, with the position it happened at if we know it
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub pos: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pos {
+            Some((line, col)) => write!(f, "{}:{}:{}: {}", self.path.display(), line, col, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_lints_are_pointed_at() {
+        let dir = std::env::temp_dir().join(format!("bach-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        fs::write(&path, "title = \"Robot\"\n\n[lints]\nmissing-return = \"allow\"\n  missing-params = \"deny\"\n").unwrap();
+
+        let err = Config::load(&path).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(err.pos, Some((5, 3)));
+        assert!(err.message.starts_with("unknown lint `missing-params`"), "{}", err.message);
+    }
+}
@@ -0,0 +1,221 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// How much of a project's public API has doc comments
+// Only what other code can see is counted: public and protected types, methods, constructors and
+// fields, inside types that can be seen themselves. Interface and annotation members are public
+// unless they say otherwise
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::doc::{Definition, Doc, Package};
+
+// Bumped whenever the report's JSON changes in a way that could break a reader
+pub const REPORT_VERSION: u32 = 1;
+
+// How many things of one kind are documented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Count {
+    pub documented: usize,
+    pub total: usize,
+}
+
+impl Count {
+    fn add(&mut self, documented: bool) {
+        self.total += 1;
+        if documented {
+            self.documented += 1;
+        }
+    }
+
+    pub fn undocumented(&self) -> usize {
+        self.total - self.documented
+    }
+
+    // As a percentage, where nothing at all counts as fully documented
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.documented as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl std::ops::AddAssign for Count {
+    fn add_assign(&mut self, other: Count) {
+        self.documented += other.documented;
+        self.total += other.total;
+    }
+}
+
+// The counts for one package, file or the whole project
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub types: Count,
+    // Constructors and annotation elements are counted as methods
+    pub methods: Count,
+    pub fields: Count,
+    // Everything together
+    pub all: Count,
+    pub percent: f64,
+}
+
+impl Stats {
+    fn add(&mut self, kind: Kind, documented: bool) {
+        match kind {
+            Kind::Type => self.types.add(documented),
+            Kind::Method => self.methods.add(documented),
+            Kind::Field => self.fields.add(documented),
+        }
+        self.all.add(documented);
+        self.percent = self.all.percent();
+    }
+}
+
+// Something public that has no doc comment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Undocumented {
+    pub file: String,
+    pub line: usize,
+    // "Class", "Method", "Field" and so on
+    pub kind: &'static str,
+    // Like `frc.robot.Robot` or `frc.robot.Robot#drive(double,double)`
+    pub name: String,
+}
+
+// The coverage of a whole project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Coverage {
+    pub version: u32,
+    pub total: Stats,
+    // By package name, empty for the default package
+    pub packages: BTreeMap<String, Stats>,
+    // By the path of the file, as it was scanned
+    pub files: BTreeMap<String, Stats>,
+    pub undocumented: Vec<Undocumented>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Type,
+    Method,
+    Field,
+}
+
+impl Coverage {
+    // Count everything in some packages
+    // They should have been scanned with Scanner::set_undocumented(), or else everything in
+    // them has a comment, apart from types kept for the sake of what's in them
+    pub fn measure(packages: &[Package]) -> Self {
+        let mut coverage = Coverage {
+            version: REPORT_VERSION,
+            ..Coverage::default()
+        };
+        for p in packages {
+            for t in &p.types {
+                coverage.ty(&p.name, t, false);
+            }
+        }
+        coverage
+    }
+
+    // `in_interface` is for types nested in an interface, which are public without saying so
+    fn ty(&mut self, pkg: &str, t: &Doc, in_interface: bool) {
+        let c = match t.def.class() {
+            Some(c) => c,
+            None => return,
+        };
+        if !visible(&c.modifiers, in_interface) {
+            return;
+        }
+        self.add(pkg, t, Kind::Type, t.full_name());
+
+        let interface = matches!(t.def, Definition::Interface(_) | Definition::Annotation(_));
+        for m in &c.fields {
+            let (kind, modifiers) = match &m.def {
+                Definition::Field(f) => (Kind::Field, &f.modifiers),
+                Definition::Method(d) => (Kind::Method, &d.modifiers),
+                _ => continue,
+            };
+            if visible(modifiers, interface) {
+                self.add(pkg, m, kind, format!("{}#{}", t.full_name(), member(m)));
+            }
+        }
+        for n in &c.nested {
+            self.ty(pkg, n, interface);
+        }
+    }
+
+    fn add(&mut self, pkg: &str, doc: &Doc, kind: Kind, name: String) {
+        let documented = doc.is_documented();
+        let file = doc.file.display().to_string();
+        self.total.add(kind, documented);
+        self.packages.entry(pkg.to_owned()).or_default().add(kind, documented);
+        self.files.entry(file.clone()).or_default().add(kind, documented);
+        if !documented {
+            self.undocumented.push(Undocumented {
+                file,
+                line: doc.line,
+                kind: doc.def.kind(),
+                name,
+            });
+        }
+    }
+
+    // Is the whole project documented at least `threshold` percent?
+    pub fn passes(&self, threshold: f64) -> bool {
+        self.total.percent >= threshold
+    }
+
+    // A plain text table of the packages and files, with the total at the bottom
+    pub fn table(&self) -> String {
+        let mut rows = vec![(String::from("Package"), None)];
+        rows.extend(self.packages.iter().map(|(name, s)| {
+            (if name.is_empty() { String::from("(default package)") } else { name.clone() }, Some(s))
+        }));
+        rows.push((String::new(), None));
+        rows.push((String::from("File"), None));
+        rows.extend(self.files.iter().map(|(name, s)| (name.clone(), Some(s))));
+        rows.push((String::new(), None));
+        rows.push((String::from("Total"), Some(&self.total)));
+
+        let width = rows.iter().map(|(n, _)| n.chars().count()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (name, stats) in rows {
+            match stats {
+                Some(s) => out += &format!("{:<width$}  {:>9}  {:>9}  {:>9}  {:>6.1}%\n",
+                                           name, fraction(s.types), fraction(s.methods), fraction(s.fields), s.percent,
+                                           width = width),
+                None if name.is_empty() => out += "\n",
+                None => out += &format!("{:<width$}  {:>9}  {:>9}  {:>9}  {:>7}\n",
+                                        name, "Types", "Methods", "Fields", "Total", width = width),
+            }
+        }
+        out
+    }
+}
+
+// Can code outside the type see this?
+fn visible(modifiers: &[String], interface: bool) -> bool {
+    let has = |m: &str| modifiers.iter().any(|x| x == m);
+    has("public") || has("protected") || (interface && !has("private"))
+}
+
+// The name of a member, with parameter types for methods
+fn member(doc: &Doc) -> String {
+    match &doc.def {
+        Definition::Method(m) => format!("{}({})", m.name, m.args.iter().map(|a| a.ty.to_string()).collect::<Vec<String>>().join(",")),
+        _ => doc.name().to_owned(),
+    }
+}
+
+// Like `3/4`
+fn fraction(c: Count) -> String {
+    format!("{}/{}", c.documented, c.total)
+}
@@ -0,0 +1,249 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// Warnings and errors about the source, pointing at where in it they are
+// Everything that goes wrong with a file without stopping the run ends up as one of these: lines
+// that couldn't be parsed, references that don't resolve and whatever the lints find
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use colored::*;
+use serde::Serialize;
+
+use crate::doc::resolve::Unresolved;
+use crate::doc::ParseError;
+
+/// How bad a [`Diagnostic`] is. Errors stop the binary writing any documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Something wrong at a place in a file.
+///
+/// It displays as plain text like rustc's messages, [`render`](Diagnostic::render) adds colors,
+/// and it serializes to the JSON that `--message-format json` prints.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The lint that found it, if it was one, like `"missing-param"`.
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub file: PathBuf,
+    /// 1-based, like an editor.
+    pub line: usize,
+    /// Not known for everything, like inside comments, which lose their columns.
+    pub column: Option<usize>,
+    /// How many characters from `column` it's about, at least 1.
+    pub length: usize,
+    /// The line it's on, once [`add_snippets`] has read it.
+    pub snippet: Option<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// A diagnostic at the start of a line, with nothing else filled in.
+    pub fn new(severity: Severity, file: &Path, line: usize, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message,
+            file: file.to_owned(),
+            line,
+            column: None,
+            length: 1,
+            snippet: None,
+            help: None,
+        }
+    }
+
+    /// A declaration in `file` that couldn't be parsed.
+    pub fn parse(file: &Path, error: &ParseError) -> Self {
+        let mut d = Diagnostic::new(Severity::Warning, file, error.line, error.message.clone());
+        d.column = Some(error.col);
+        d
+    }
+
+    /// A link to something that doesn't exist.
+    pub fn unresolved(u: &Unresolved) -> Self {
+        Diagnostic::new(Severity::Warning, &u.file, u.line, format!("unresolved reference `{}`", u.reference))
+    }
+
+    /// Render it like rustc does, colored if [`colored`] thinks the terminal wants it.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, true).expect("writing to a String can't fail");
+        out
+    }
+
+    // Like rustc:
+    //
+    // warning[missing-param]: parameter `b` of `drive` has no @param
+    //   --> src/Robot.java:12:35
+    //    |
+    // 12 |     public void drive(double a, double b) {
+    //    |                                        ^
+    //    = help: add `@param b ...` to the comment
+    fn write(&self, f: &mut impl fmt::Write, color: bool) -> fmt::Result {
+        let paint = |s: &str, c: Color| if color { s.color(c).bold().to_string() } else { s.to_owned() };
+        let (severity, c) = match self.severity {
+            Severity::Warning => ("warning", Color::Yellow),
+            Severity::Error => ("error", Color::Red),
+        };
+        let head = match self.code {
+            Some(code) => format!("{}[{}]", severity, code),
+            None => severity.to_owned(),
+        };
+        let message = if color { self.message.bold().to_string() } else { self.message.clone() };
+        writeln!(f, "{} {}", paint(&format!("{}:", head), c), message)?;
+
+        // The gutter is as wide as the line number
+        let gutter = " ".repeat(self.line.to_string().len());
+        let bar = paint("|", Color::Blue);
+        match self.column {
+            Some(col) => write!(f, "{}{} {}:{}:{}", gutter, paint("-->", Color::Blue), self.file.display(), self.line, col)?,
+            None => write!(f, "{}{} {}:{}", gutter, paint("-->", Color::Blue), self.file.display(), self.line)?,
+        }
+
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{} {}", gutter, bar)?;
+            write!(f, "\n{} {} {}", paint(&self.line.to_string(), Color::Blue), bar, snippet)?;
+            if let Some(col) = self.column {
+                // Keep any tabs so the carets line up with what's above them
+                let indent = snippet
+                    .chars()
+                    .take(col.saturating_sub(1))
+                    .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+                write!(f, "\n{} {} {}{}", gutter, bar, indent, paint(&"^".repeat(self.length.max(1)), c))?;
+            }
+        }
+        if let Some(help) = &self.help {
+            write!(f, "\n{} {} {}", gutter, paint("= help:", Color::Blue), help)?;
+        }
+        Ok(())
+    }
+}
+
+// The same as render(), without colors
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+/// Fill in the snippets, reading each file once.
+/// Files that can't be read any more just go without.
+pub fn add_snippets(diagnostics: &mut [Diagnostic]) {
+    let mut files: HashMap<PathBuf, Option<Vec<String>>> = HashMap::new();
+    for d in diagnostics.iter_mut() {
+        let lines = files
+            .entry(d.file.clone())
+            .or_insert_with(|| fs::read_to_string(&d.file).ok().map(|s| s.lines().map(|l| l.trim_end().to_owned()).collect()));
+        d.snippet = lines.as_ref().and_then(|l| l.get(d.line.wrapping_sub(1))).cloned();
+    }
+}
+
+// The 1-based line and column of a byte offset into some text, with columns counted in characters
+// like every other column here
+pub(crate) fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset.min(src.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) + 1;
+    (line, col)
+}
+
+/// Put diagnostics in the order they are in the files, keeping the order of ones in the same place.
+pub fn sort(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize, column: Option<usize>, snippet: &str) -> Diagnostic {
+        let mut d = Diagnostic::new(Severity::Warning, Path::new("src/Robot.java"), line, String::from("bad"));
+        d.column = column;
+        d.snippet = Some(snippet.to_owned());
+        d
+    }
+
+    #[test]
+    fn renders_like_rustc() {
+        let mut d = at(12, Some(39), "    public void drive(double a, double b) {");
+        d.code = Some("missing-param");
+        d.message = String::from("parameter `b` of `drive` has no @param");
+        d.help = Some(String::from("add `@param b ...` to the comment"));
+        assert_eq!(d.to_string(), "\
+warning[missing-param]: parameter `b` of `drive` has no @param
+  --> src/Robot.java:12:39
+   |
+12 |     public void drive(double a, double b) {
+   |                                       ^
+   = help: add `@param b ...` to the comment");
+    }
+
+    #[test]
+    fn gutter_fits_the_line_number() {
+        let mut d = at(1234, Some(5), "int speed;");
+        d.severity = Severity::Error;
+        d.length = 5;
+        assert_eq!(d.to_string(), "\
+error: bad
+    --> src/Robot.java:1234:5
+     |
+1234 | int speed;
+     |     ^^^^^");
+
+        let d = Diagnostic::new(Severity::Warning, Path::new("Robot.java"), 7, String::from("unresolved reference `Nowhere`"));
+        assert_eq!(d.to_string(), "warning: unresolved reference `Nowhere`\n --> Robot.java:7");
+    }
+
+    #[test]
+    fn carets_line_up_with_tabs_and_unicode() {
+        let d = at(3, Some(4), "\t\tint x;");
+        assert_eq!(d.to_string().lines().last(), Some("  | \t\t ^"));
+
+        // Columns count characters, not bytes
+        let d = at(3, Some(17), "String s = \"é\"; int x;");
+        assert_eq!(d.to_string().lines().last(), Some("  |                 ^"));
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        let src = "a = 1\nname = \"é\" x";
+        assert_eq!(line_col(src, 0), (1, 1));
+        assert_eq!(line_col(src, 6), (2, 1));
+        assert_eq!(line_col(src, src.find('x').unwrap()), (2, 12));
+        assert_eq!(line_col(src, src.len() + 10), (2, 13));
+    }
+
+    #[test]
+    fn sorts_by_place() {
+        let mut diagnostics = vec![
+            at(10, Some(3), "b"),
+            Diagnostic::new(Severity::Warning, Path::new("src/Arm.java"), 40, String::from("first")),
+            at(2, None, "a"),
+            at(10, Some(1), "c"),
+            at(2, None, "d"),
+        ];
+        sort(&mut diagnostics);
+        let order = diagnostics.iter().map(|d| (d.file.to_str().unwrap(), d.line, d.snippet.as_deref())).collect::<Vec<_>>();
+        assert_eq!(order, vec![
+            ("src/Arm.java", 40, None),
+            ("src/Robot.java", 2, Some("a")),
+            ("src/Robot.java", 2, Some("d")),
+            ("src/Robot.java", 10, Some("c")),
+            ("src/Robot.java", 10, Some("b")),
+        ]);
+    }
+}
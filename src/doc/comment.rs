@@ -0,0 +1,401 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::ops::Range;
+
+// One `@tag` at the start of a line and the text that goes with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockTag {
+    // `@param name text`, the name can also be a type parameter like `<T>`
+    Param { name: String, text: String },
+    Return(String),
+    // `@throws Type text`, or the older `@exception`
+    Throws { ty: String, text: String },
+    See(String),
+    Since(String),
+    Deprecated(String),
+    Author(String),
+    // Anything we don't know, kept so nothing is lost
+    Other { name: String, text: String },
+}
+
+impl BlockTag {
+    // Build a tag from its name (without the `@`) and everything after it
+    fn new(name: &str, text: String) -> Self {
+        match name {
+            "param" => {
+                let (name, text) = split_word(&text);
+                BlockTag::Param { name, text }
+            },
+            "throws" | "exception" => {
+                let (ty, text) = split_word(&text);
+                BlockTag::Throws { ty, text }
+            },
+            "return" => BlockTag::Return(text),
+            "see" => BlockTag::See(text),
+            "since" => BlockTag::Since(text),
+            "deprecated" => BlockTag::Deprecated(text),
+            "author" => BlockTag::Author(text),
+            _ => BlockTag::Other { name: name.to_owned(), text },
+        }
+    }
+
+    // Add another line onto the end of the tag's text
+    fn extend(&mut self, line: &str) {
+        let text = match self {
+            BlockTag::Param { text, .. } | BlockTag::Throws { text, .. } | BlockTag::Other { text, .. } => text,
+            BlockTag::Return(text) | BlockTag::See(text) | BlockTag::Since(text)
+                | BlockTag::Deprecated(text) | BlockTag::Author(text) => text,
+        };
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+}
+
+// A doc comment split up into its parts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comment {
+    // The first sentence, for tables of contents
+    pub summary: String,
+    // Every line that isn't part of a block tag, the summary included
+    pub description: Vec<String>,
+    pub tags: Vec<BlockTag>,
+}
+
+impl Comment {
+    // Split up the lines of a comment
+    // A block tag runs until the next tag or a blank line, and anything after a blank line
+    // goes back into the description so `///` comments can carry on after their tags
+    // Fenced code blocks are kept as they are, so `@Override` in an example isn't a tag
+    pub fn parse(lines: &[String]) -> Self {
+        let mut comment = Comment::default();
+        let mut current: Option<BlockTag> = None;
+
+        for (raw, code) in lines.iter().zip(fenced(lines)) {
+            let line = raw.trim();
+            if code {
+                comment.tags.extend(current.take());
+                comment.description.push(raw.trim_end().to_owned());
+            } else if let Some(rest) = line.strip_prefix('@') {
+                comment.tags.extend(current.take());
+                let (name, text) = split_word(rest);
+                current = Some(BlockTag::new(&name, text));
+            } else if line.is_empty() {
+                // Keep one blank line between paragraphs, however many there were
+                comment.tags.extend(current.take());
+                if comment.description.last().map(|l| !l.is_empty()).unwrap_or(false) {
+                    comment.description.push(String::new());
+                }
+            } else if let Some(tag) = &mut current {
+                tag.extend(line);
+            } else {
+                // Keep the indent, it means something to code examples
+                comment.description.push(raw.trim_end().to_owned());
+            }
+        }
+        comment.tags.extend(current);
+
+        // A blank line at the end is left over from tags
+        if comment.description.last().map(|l| l.is_empty()).unwrap_or(false) {
+            comment.description.pop();
+        }
+
+        comment.summary = first_sentence(&comment.description);
+        comment
+    }
+
+    // The text of the `@param` for a parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find_map(|t| match t {
+            BlockTag::Param { name: n, text } if n == name => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().filter_map(|t| match t {
+            BlockTag::Param { name, text } => Some((name.as_str(), text.as_str())),
+            _ => None,
+        })
+    }
+
+    pub fn returns(&self) -> Option<&str> {
+        self.tags.iter().find_map(|t| match t {
+            BlockTag::Return(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn throws(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().filter_map(|t| match t {
+            BlockTag::Throws { ty, text } => Some((ty.as_str(), text.as_str())),
+            _ => None,
+        })
+    }
+
+    pub fn deprecated(&self) -> Option<&str> {
+        self.tags.iter().find_map(|t| match t {
+            BlockTag::Deprecated(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+// Which lines are part of a fenced code block, fences included
+// A block is closed by a fence of the same character at least as long as the one that opened it,
+// or else runs to the end of the comment
+pub fn fenced(lines: &[String]) -> Vec<bool> {
+    let mut open: Option<(char, usize)> = None;
+    lines
+        .iter()
+        .map(|l| {
+            let fence = fence(l.trim());
+            match (open, fence) {
+                (Some((c, n)), Some((fc, len, bare))) if fc == c && len >= n && bare => open = None,
+                (Some(_), _) => (),
+                (None, Some((c, n, _))) => open = Some((c, n)),
+                (None, None) => return false,
+            }
+            true
+        })
+        .collect()
+}
+
+// A line that's a code fence: its character, how long it is, and whether there's nothing after it
+fn fence(line: &str) -> Option<(char, usize, bool)> {
+    let c = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let n = line.chars().take_while(|x| *x == c).count();
+    if n >= 3 {
+        Some((c, n, line[n..].trim().is_empty()))
+    } else {
+        None
+    }
+}
+
+// Split the first word off some text, like the name of a `@param`
+fn split_word(text: &str) -> (String, String) {
+    let text = text.trim();
+    match text.find(char::is_whitespace) {
+        Some(i) => (text[..i].to_owned(), text[i..].trim().to_owned()),
+        None => (text.to_owned(), String::new()),
+    }
+}
+
+// The first sentence of the first paragraph, which ends at a `.` followed by whitespace
+fn first_sentence(lines: &[String]) -> String {
+    let paragraph = lines
+        .iter()
+        .take_while(|l| !l.is_empty())
+        .map(|l| l.trim())
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    let mut chars = paragraph.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '.' && chars.peek().map(|(_, n)| n.is_whitespace()).unwrap_or(true) {
+            return paragraph[..=i].to_owned();
+        }
+    }
+    paragraph
+}
+
+// A piece of doc text, with its inline `{@...}` tags picked out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    // Text as it was written
+    Text(String),
+    // `{@code ...}`, shown as code
+    Code(String),
+    // `{@literal ...}`, shown as it was written without being treated as markup
+    Literal(String),
+    // `{@link ...}` and `{@linkplain ...}`, a reference like `Foo#bar(int)` and an optional label
+    Link { target: String, label: Option<String>, plain: bool },
+    // `{@value}`, for the constant being documented, or `{@value Foo#BAR}`
+    Value(Option<String>),
+    // Anything we don't know, like `{@inheritDoc}`
+    Other { name: String, text: String },
+}
+
+// Split some text up into plain text and inline tags
+// A tag's text runs to its matching `}`, so `{@code Map<K, V> m = {}}` works
+pub fn parse_inline(text: &str) -> Vec<Inline> {
+    inline_spans(text).into_iter().map(|(_, i)| i).collect()
+}
+
+// Every reference a comment makes, from `{@link}`, `{@linkplain}`, `{@value}` and `@see`
+// They come with the index of the line they're on
+pub fn references(lines: &[String]) -> Vec<(usize, String)> {
+    let text = lines.join("\n");
+    let line_of = |offset: usize| text[..offset].matches('\n').count();
+
+    let mut refs = Vec::new();
+    for (span, i) in inline_spans(&text) {
+        match i {
+            Inline::Link { target, .. } | Inline::Value(Some(target)) => refs.push((line_of(span.start), target)),
+            _ => (),
+        }
+    }
+
+    // `@see "Some text"` and `@see <a href=...>` aren't references
+    for (n, (line, code)) in lines.iter().zip(fenced(lines)).enumerate() {
+        if code {
+            continue;
+        }
+        if let Some(rest) = line.trim().strip_prefix("@see") {
+            let rest = rest.trim();
+            if !rest.is_empty() && !rest.starts_with('"') && !rest.starts_with('<') {
+                refs.push((n, split_reference(rest).0));
+            }
+        }
+    }
+
+    refs.sort_by_key(|(n, _)| *n);
+    refs
+}
+
+// Split a reference off the front of some text, leaving the label
+// Spaces inside the parameter list of a method, like `Foo#bar(int, double)`, are part of the reference
+pub fn split_reference(text: &str) -> (String, Option<String>) {
+    let text = text.trim();
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                let label = text[i..].trim();
+                return (text[..i].to_owned(), Some(label.to_owned()).filter(|l| !l.is_empty()));
+            },
+            _ => (),
+        }
+    }
+    (text.to_owned(), None)
+}
+
+// The pieces of some text, with where each one is in it
+pub fn inline_spans(text: &str) -> Vec<(Range<usize>, Inline)> {
+    let mut out = Vec::new();
+    let mut rest = 0;
+
+    while let Some(found) = text[rest..].find("{@") {
+        let start = rest + found;
+
+        // Find the `}` that matches, counting any braces inside
+        let mut depth = 0usize;
+        let mut end = None;
+        for (i, c) in text[start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(start + i);
+                        break;
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        // An unclosed tag is just text
+        let end = match end {
+            Some(e) => e,
+            None => break,
+        };
+
+        if start > rest {
+            out.push((rest..start, Inline::Text(text[rest..start].to_owned())));
+        }
+
+        let (name, body) = split_word(&text[start + 2..end]);
+        let tag = match name.as_str() {
+            "code" => Inline::Code(body),
+            "literal" => Inline::Literal(body),
+            "link" | "linkplain" => {
+                let (target, label) = split_reference(&body);
+                Inline::Link { target, label, plain: name == "linkplain" }
+            },
+            "value" => Inline::Value(Some(body).filter(|b| !b.is_empty())),
+            _ => Inline::Other { name, text: body },
+        };
+        out.push((start..end + 1, tag));
+
+        rest = end + 1;
+    }
+
+    if rest < text.len() {
+        out.push((rest..text.len(), Inline::Text(text[rest..].to_owned())));
+    }
+    out
+}
+
+// Some doc text with its inline tags flattened into plain text, for places html can't go
+pub fn plain_text(text: &str) -> String {
+    parse_inline(text)
+        .into_iter()
+        .map(|i| match i {
+            Inline::Text(t) | Inline::Code(t) | Inline::Literal(t) => t,
+            Inline::Link { target, label, .. } => label.unwrap_or_else(|| target.trim_start_matches('#').replace('#', ".")),
+            Inline::Value(target) => target.unwrap_or_default(),
+            Inline::Other { text, .. } => text,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn tags_inside_fences_are_code() {
+        let example = [
+            "```java",
+            "@Override",
+            "public void teleopPeriodic() {",
+            "",
+            "",
+            "    drive();",
+            "}",
+            "```",
+        ];
+        let mut text = vec!["Drives the robot."];
+        text.extend(&example);
+        text.extend(&["After the example.", "@param speed how fast", "@see Other"]);
+        let comment = Comment::parse(&text.iter().map(|l| l.to_string()).collect::<Vec<String>>());
+
+        let mut description = vec!["Drives the robot."];
+        description.extend(&example);
+        description.push("After the example.");
+        assert_eq!(comment.description, description);
+        assert_eq!(comment.tags, vec![
+            BlockTag::Param { name: String::from("speed"), text: String::from("how fast") },
+            BlockTag::See(String::from("Other")),
+        ]);
+        assert_eq!(comment.summary, "Drives the robot.");
+    }
+
+    #[test]
+    fn unclosed_fences_run_to_the_end() {
+        let comment = Comment::parse(&lines("Example.\n~~~~\n@see Nothing\n~~~\n@return nothing"));
+        assert!(comment.tags.is_empty());
+        assert_eq!(comment.description.len(), 5);
+        assert!(references(&lines("~~~\n@see Nothing\n~~~~\n@see Something")).iter().all(|(n, _)| *n == 3));
+    }
+
+    #[test]
+    fn tags_end_at_blank_lines() {
+        let comment = Comment::parse(&lines("Summary. More.\n@return the\nanswer\n\nBack to the description."));
+        assert_eq!(comment.returns(), Some("the answer"));
+        assert_eq!(comment.description, lines("Summary. More.\n\nBack to the description."));
+        assert_eq!(comment.summary, "Summary.");
+    }
+}
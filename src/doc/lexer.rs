@@ -0,0 +1,286 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use super::parser::ParseError;
+
+// What sort of token something is
+// Keywords are lexed as identifiers and told apart by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Ident,
+    // A single character of punctuation, or `...`
+    Punct,
+    // String, character, text block and number literals
+    Literal,
+    // A `///` line or a `/** */` block, only kept by lex_file()
+    Doc,
+}
+
+// A single token with the position it started at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    // 1-based line and column
+    pub line: usize,
+    pub col: usize,
+    // Byte offsets into the source
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    // Is this the punctuation `c`?
+    pub fn is(&self, c: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == c
+    }
+
+    // Is this the identifier or keyword `s`?
+    pub fn is_word(&self, s: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text == s
+    }
+}
+
+// Walks over the source one character at a time keeping track of where it is
+struct Cursor<'s> {
+    src: &'s str,
+    pos: usize,
+    line: usize,
+    col: usize,
+}
+
+impl<'s> Cursor<'s> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.src[self.pos..].chars().nth(n)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.src[self.pos..].starts_with(s)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(self.line, self.col, message)
+    }
+}
+
+// Break Java source up into tokens, throwing away whitespace and comments
+pub fn lex(src: &str) -> Result<Vec<Token>, ParseError> {
+    tokenize(src, false)
+}
+
+// Break a whole file up into tokens, keeping documentation comments
+pub fn lex_file(src: &str) -> Result<Vec<Token>, ParseError> {
+    tokenize(src, true)
+}
+
+fn tokenize(src: &str, keep_docs: bool) -> Result<Vec<Token>, ParseError> {
+    let mut cur = Cursor { src, pos: 0, line: 1, col: 1 };
+    let mut tokens = Vec::new();
+
+    while let Some(c) = cur.peek() {
+        if c.is_whitespace() {
+            cur.bump();
+            continue;
+        }
+
+        let (start, line, col) = (cur.pos, cur.line, cur.col);
+
+        // Comments
+        let doc = if cur.starts_with("//") {
+            let doc = cur.starts_with("///");
+            while !matches!(cur.peek(), Some('\n') | None) {
+                cur.bump();
+            }
+            Some(doc)
+        } else if cur.starts_with("/*") {
+            // `/**/` is just an empty comment
+            let doc = cur.starts_with("/**") && !cur.starts_with("/**/");
+            cur.bump();
+            cur.bump();
+            while !cur.starts_with("*/") {
+                if cur.bump().is_none() {
+                    return Err(ParseError::new(line, col, "unterminated comment"));
+                }
+            }
+            cur.bump();
+            cur.bump();
+            Some(doc)
+        } else {
+            None
+        };
+
+        if let Some(doc) = doc {
+            if doc && keep_docs {
+                tokens.push(Token {
+                    kind: TokenKind::Doc,
+                    text: src[start..cur.pos].to_owned(),
+                    line,
+                    col,
+                    start,
+                    end: cur.pos,
+                });
+            }
+            continue;
+        }
+
+        let kind = if c.is_alphabetic() || c == '_' || c == '$' {
+            while matches!(cur.peek(), Some(c) if c.is_alphanumeric() || c == '_' || c == '$') {
+                cur.bump();
+            }
+            TokenKind::Ident
+        } else if c.is_ascii_digit() || (c == '.' && matches!(cur.peek_at(1), Some(d) if d.is_ascii_digit())) {
+            // Close enough for every number format Java has, we never need the value
+            // A sign only goes after the exponent, which is `e` in decimal and `p` in hex, so
+            // `0xE+1` is a sum
+            let hex = matches!(cur.src[start..].get(..2), Some("0x") | Some("0X"));
+            while let Some(c) = cur.peek() {
+                let exponent = match cur.src[..cur.pos].chars().last() {
+                    Some('e') | Some('E') => !hex,
+                    Some('p') | Some('P') => hex,
+                    _ => false,
+                };
+                if !(c.is_alphanumeric() || c == '_' || c == '.' || ((c == '+' || c == '-') && exponent)) {
+                    break;
+                }
+                cur.bump();
+            }
+            TokenKind::Literal
+        } else if cur.starts_with("\"\"\"") {
+            // Text block
+            cur.bump();
+            cur.bump();
+            cur.bump();
+            while !cur.starts_with("\"\"\"") {
+                if cur.peek() == Some('\\') {
+                    cur.bump();
+                }
+                if cur.bump().is_none() {
+                    return Err(ParseError::new(line, col, "unterminated text block"));
+                }
+            }
+            cur.bump();
+            cur.bump();
+            cur.bump();
+            TokenKind::Literal
+        } else if c == '"' || c == '\'' {
+            cur.bump();
+            loop {
+                match cur.bump() {
+                    Some('\\') => {
+                        cur.bump();
+                    },
+                    Some(q) if q == c => break,
+                    Some('\n') | None => return Err(ParseError::new(line, col, "unterminated literal")),
+                    Some(_) => (),
+                }
+            }
+            TokenKind::Literal
+        } else if cur.starts_with("...") {
+            cur.bump();
+            cur.bump();
+            cur.bump();
+            TokenKind::Punct
+        } else if c.is_ascii_punctuation() {
+            cur.bump();
+            TokenKind::Punct
+        } else {
+            return Err(cur.error(&format!("unexpected character `{}`", c)));
+        };
+
+        tokens.push(Token {
+            kind,
+            text: src[start..cur.pos].to_owned(),
+            line,
+            col,
+            start,
+            end: cur.pos,
+        });
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.text.as_str()).collect()
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = lex("void log(String fmt, Object... args)").unwrap();
+        assert_eq!(texts(&tokens), vec!["void", "log", "(", "String", "fmt", ",", "Object", "...", "args", ")"]);
+        assert_eq!(tokens[7].kind, TokenKind::Punct);
+
+        // Generics are lexed a character at a time, so `>>` closes two of them
+        let tokens = lex("Map<String, List<Integer>> m").unwrap();
+        assert_eq!(texts(&tokens)[7..9], [">", ">"]);
+    }
+
+    #[test]
+    fn positions() {
+        let src = "int a;\n  double b;";
+        let tokens = lex(src).unwrap();
+        let b = &tokens[4];
+        assert_eq!(b.text, "b");
+        assert_eq!((b.line, b.col), (2, 10));
+        assert_eq!(&src[b.start..b.end], "b");
+    }
+
+    #[test]
+    fn literals() {
+        let src = r#"x = 0x1F + 1.5e-3f + 0xE+1 + 0x1.8p-2 + 'a' + "a \" b" + """
+            a "text" \""" block
+            """;"#;
+        let tokens = lex(src).unwrap();
+        let literals = tokens.iter().filter(|t| t.kind == TokenKind::Literal).map(|t| t.text.as_str()).collect::<Vec<&str>>();
+        assert_eq!(literals, vec!["0x1F", "1.5e-3f", "0xE", "1", "0x1.8p-2", "'a'", r#""a \" b""#, &src[src.find("\"\"\"").unwrap()..src.len() - 1]]);
+        assert!(tokens.last().unwrap().is(";"));
+    }
+
+    #[test]
+    fn comments() {
+        let src = "/** Doc. */ // line\n/* block */ /**/ int /// more\nx;";
+        assert_eq!(texts(&lex(src).unwrap()), vec!["int", "x", ";"]);
+
+        let tokens = lex_file(src).unwrap();
+        assert_eq!(texts(&tokens), vec!["/** Doc. */", "int", "/// more", "x", ";"]);
+        assert_eq!(tokens[0].kind, TokenKind::Doc);
+        assert_eq!(tokens[2].kind, TokenKind::Doc);
+    }
+
+    #[test]
+    fn malformed() {
+        let err = lex("int a;\n/* never closed").unwrap_err();
+        assert_eq!((err.line, err.col), (2, 1));
+        assert_eq!(err.message, "unterminated comment");
+
+        let err = lex("String s = \"open\n;").unwrap_err();
+        assert_eq!((err.line, err.col), (1, 12));
+        assert_eq!(err.message, "unterminated literal");
+
+        assert!(lex("String s = \"\"\" never closed").is_err());
+        assert!(lex("int a = 1 € 2;").is_err());
+    }
+}
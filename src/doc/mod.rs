@@ -0,0 +1,482 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+pub mod comment;
+pub mod lexer;
+pub mod parser;
+pub mod resolve;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+pub use comment::{BlockTag, Comment, Inline};
+pub use parser::ParseError;
+
+// Something that is documented
+#[derive(Debug, Clone)]
+pub struct Doc<'a> {
+    pub tag: Vec<String>,
+    pub def: Definition<'a>,
+    pub pkg: String,
+    // Names of the types this is inside of, outermost first
+    pub scope: Vec<String>,
+    // Where it came from, with the line the text of its comment starts on
+    pub file: PathBuf,
+    pub line: usize,
+    // The line and column the declaration itself starts at
+    pub pos: (usize, usize),
+    // What the file imports, like `java.util.List` or `java.util.*`, only kept on top level types
+    pub imports: Vec<String>,
+    _marker: PhantomData<&'a ()>,
+}
+
+macro_rules! is {
+    ( $s:expr, $t:pat ) => {
+        match $s.def {
+            $t => true,
+            _ => false,
+        }
+    };
+}
+
+impl<'a> Doc<'a> {
+    pub fn new(pkg: String) -> Self {
+        Doc {
+            tag: Vec::new(),
+            def: Definition::None,
+            pkg,
+            scope: Vec::new(),
+            file: PathBuf::new(),
+            line: 0,
+            pos: (0, 0),
+            imports: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    // Push a new string to the tag
+    pub fn push(&mut self, s: String) {
+        self.tag.push(s);
+    }
+
+    pub fn push_field(&mut self, f: Doc<'a>) {
+        match self.def.class_mut() {
+            Some(c) => c.push_field(f),
+            None => panic!("Not a class"),
+        }
+    }
+
+    pub fn push_nested(&mut self, t: Doc<'a>) {
+        match self.def.class_mut() {
+            Some(c) => c.push_nested(t),
+            None => panic!("Not a class"),
+        }
+    }
+
+    // Set the definition 
+    pub fn set_def(&mut self, def: Definition<'a>) {
+        self.def = def;
+    }
+
+    pub fn is_class(&self) -> bool {
+        is!(&self, Definition::Class(_))
+    }
+
+    // Is this any kind of type: a class, interface, enum, record or annotation type?
+    pub fn is_type(&self) -> bool {
+        self.def.is_type()
+    }
+
+    /*
+    pub fn is_field(&self) -> bool {
+        is!(&self, Definition::Field(_))
+    }
+
+    pub fn is_method(&self) -> bool {
+        is!(&self, Definition::Method(_))
+    }
+    */
+
+    pub fn name(&self) -> &str {
+        match &self.def {
+            Definition::Field(f) => &f.name,
+            Definition::Method(m) => &m.name,
+            Definition::Constant(c) => &c.name,
+            d => d.class().map(|c| c.get_name()).unwrap_or(""),
+        }
+    }
+
+    // The name including the package, like `frc.robot.Outer.Inner`
+    pub fn full_name(&self) -> String {
+        if self.pkg.is_empty() {
+            self.qualified_name()
+        } else {
+            format!("{}.{}", self.pkg, self.qualified_name())
+        }
+    }
+
+    // The name including any types it's nested in, like `Outer.Inner`
+    pub fn qualified_name(&self) -> String {
+        let mut name = self.scope.join(".");
+        if !name.is_empty() {
+            name.push('.');
+        }
+        name + self.name()
+    }
+
+    // Does this have a doc comment of its own?
+    pub fn is_documented(&self) -> bool {
+        !self.tag.is_empty()
+    }
+
+    // The doc comment split into its summary, description and block tags
+    pub fn comment(&self) -> Comment {
+        Comment::parse(&self.tag)
+    }
+
+    // This type and every type nested inside it, outermost first
+    pub fn all_types(&self) -> Vec<&Doc<'a>> {
+        let mut types = Vec::new();
+        if let Some(c) = self.def.class() {
+            types.push(self);
+            for n in &c.nested {
+                types.append(&mut n.all_types());
+            }
+        }
+        types
+    }
+}
+
+// A Java package and the documented types in it
+#[derive(Debug, Clone)]
+pub struct Package<'a> {
+    // Empty for the unnamed package
+    pub name: String,
+    // The comment on the `package` line of package-info.java
+    pub tag: Vec<String>,
+    // Top level types, with nested types inside them
+    pub types: Vec<Doc<'a>>,
+}
+
+impl<'a> Package<'a> {
+    pub fn new(name: String) -> Self {
+        Package {
+            name,
+            tag: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    // Sort top level types into their packages, ordered by name
+    // `overviews` are the package-info.java comments, by package name
+    pub fn group(docs: Vec<Doc<'a>>, overviews: Vec<(String, Vec<String>)>) -> Vec<Package<'a>> {
+        let mut packages: BTreeMap<String, Package<'a>> = BTreeMap::new();
+        for d in docs {
+            packages.entry(d.pkg.clone())
+                .or_insert_with(|| Package::new(d.pkg.clone()))
+                .types
+                .push(d);
+        }
+        // A package split over several roots can have more than one package-info.java, and the
+        // first one wins
+        for (name, tag) in overviews {
+            let p = packages.entry(name.clone()).or_insert_with(|| Package::new(name));
+            if p.tag.is_empty() {
+                p.tag = tag;
+            }
+        }
+        packages.into_values().collect()
+    }
+
+    // The package overview split into its summary, description and block tags
+    pub fn comment(&self) -> Comment {
+        Comment::parse(&self.tag)
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "(default package)"
+        } else {
+            &self.name
+        }
+    }
+
+    // What links to the package use, which can't have spaces in it
+    pub fn id(&self) -> &str {
+        if self.name.is_empty() {
+            "default"
+        } else {
+            &self.name
+        }
+    }
+
+    // Every type in the package, nested ones included
+    pub fn all_types(&self) -> Vec<&Doc<'a>> {
+        self.types.iter().flat_map(|t| t.all_types()).collect()
+    }
+}
+
+// An actual definition
+// Every kind of type shares ClassDef, and only fills in the parts that apply to it
+#[derive(Debug, Clone)]
+pub enum Definition<'a> {
+    Class(ClassDef<'a>),
+    Interface(ClassDef<'a>),
+    Enum(ClassDef<'a>),
+    Record(ClassDef<'a>),
+    // `@interface`
+    Annotation(ClassDef<'a>),
+    Field(FieldDef),
+    Method(MethodDef),
+    // A constant inside an enum
+    Constant(ConstantDef),
+    None,
+}
+
+impl<'a> Definition<'a> {
+    // Parse a declaration, stopping at the `{`, `;` or `=` that ends it
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        parser::parse(s)
+    }
+
+    pub fn is_type(&self) -> bool {
+        self.class().is_some()
+    }
+
+    // The type being defined, if this is a type
+    pub fn class(&self) -> Option<&ClassDef<'a>> {
+        match self {
+            Definition::Class(c) | Definition::Interface(c) | Definition::Enum(c)
+                | Definition::Record(c) | Definition::Annotation(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn class_mut(&mut self) -> Option<&mut ClassDef<'a>> {
+        match self {
+            Definition::Class(c) | Definition::Interface(c) | Definition::Enum(c)
+                | Definition::Record(c) | Definition::Annotation(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    // What this is called in headings, like "Class" or "Enum"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Definition::Class(_) => "Class",
+            Definition::Interface(_) => "Interface",
+            Definition::Enum(_) => "Enum",
+            Definition::Record(_) => "Record",
+            Definition::Annotation(_) => "Annotation Type",
+            Definition::Field(_) => "Field",
+            Definition::Method(m) if m.is_constructor() => "Constructor",
+            Definition::Method(_) => "Method",
+            Definition::Constant(_) => "Enum Constant",
+            Definition::None => "",
+        }
+    }
+}
+
+// A reference to a type, like `int[]` or `Map<String, List<Integer>>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRef {
+    // The name as written, possibly qualified, or `?` for a wildcard
+    pub name: String,
+    pub args: Vec<TypeRef>,
+    // Number of `[]` after it
+    pub dims: usize,
+    // The `extends`/`super` bound of a wildcard or type parameter
+    pub bound: Option<Bound>,
+}
+
+// A bound on a wildcard or type parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bound {
+    // `extends A & B`
+    Extends(Vec<TypeRef>),
+    // `super A`
+    Super(Box<TypeRef>),
+}
+
+impl TypeRef {
+    pub fn new(name: String) -> Self {
+        TypeRef {
+            name,
+            args: Vec::new(),
+            dims: 0,
+            bound: None,
+        }
+    }
+
+    // The name without any package or outer class in front of it
+    pub fn simple_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.args.is_empty() {
+            write!(f, "<{}>", join(&self.args, ", "))?;
+        }
+        match &self.bound {
+            Some(Bound::Extends(b)) => write!(f, " extends {}", join(b, " & "))?,
+            Some(Bound::Super(b)) => write!(f, " super {}", b)?,
+            None => (),
+        }
+        for _ in 0..self.dims {
+            write!(f, "[]")?;
+        }
+        Ok(())
+    }
+}
+
+// Join anything displayable with a separator
+pub(crate) fn join<T: fmt::Display>(items: &[T], sep: &str) -> String {
+    items.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(sep)
+}
+
+// A variable wrapper struct, used for method parameters
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: String,
+    pub ty: TypeRef,
+    // Is this a `Type... name` parameter?
+    pub varargs: bool,
+}
+
+impl Variable {
+    pub fn new(name: String, ty: TypeRef, varargs: bool) -> Self {
+        Variable {
+            name,
+            ty,
+            varargs
+        }
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.varargs {
+            write!(f, "{}... {}", self.ty, self.name)
+        } else {
+            write!(f, "{} {}", self.ty, self.name)
+        }
+    }
+}
+
+// A method definition
+// Constructors are methods without a return type
+#[derive(Debug, Clone)]
+pub struct MethodDef {
+    pub name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<String>,
+    pub type_params: Vec<TypeRef>,
+    pub ret: Option<TypeRef>,
+    pub args: Vec<Variable>,
+    pub throws: Vec<TypeRef>,
+    // The `default` value of an annotation type element
+    pub default: Option<String>,
+    pub raw: String,
+}
+
+impl MethodDef {
+    pub fn is_constructor(&self) -> bool {
+        self.ret.is_none()
+    }
+}
+
+// A field definition
+#[derive(Debug, Clone)]
+pub struct FieldDef {
+    pub name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<String>,
+    pub ty: TypeRef,
+    // The initializer, kept for `{@value}`
+    pub value: Option<String>,
+    pub raw: String,
+}
+
+// A constant in an enum, like `RED` or `FAST(1.0)`
+#[derive(Debug, Clone)]
+pub struct ConstantDef {
+    pub name: String,
+    pub annotations: Vec<String>,
+    // The arguments passed to the enum's constructor, as written
+    pub args: Option<String>,
+    pub raw: String,
+}
+
+// A class definition, also used for the other kinds of type
+#[derive(Debug, Clone)]
+pub struct ClassDef<'a> {
+    name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<String>,
+    pub type_params: Vec<TypeRef>,
+    pub extends: Vec<TypeRef>,
+    pub implements: Vec<TypeRef>,
+    // The components in a record's header
+    pub components: Vec<Variable>,
+    raw: String,
+    // Documented fields, methods and constructors
+    pub fields: Vec<Doc<'a>>,
+    // The constants of an enum
+    pub constants: Vec<Doc<'a>>,
+    // Types declared inside this one
+    pub nested: Vec<Doc<'a>>,
+}
+
+impl<'a> ClassDef<'a> {
+    pub fn new(name: String, raw: String) -> Self {
+        ClassDef {
+            name,
+            modifiers: Vec::new(),
+            annotations: Vec::new(),
+            type_params: Vec::new(),
+            extends: Vec::new(),
+            implements: Vec::new(),
+            components: Vec::new(),
+            raw,
+            fields: Vec::new(),
+            constants: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // Return the raw definition straight from the source code
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn push_field(&mut self, f: Doc<'a>) {
+        self.fields.push(f);
+    }
+
+    pub fn push_constant(&mut self, c: Doc<'a>) {
+        self.constants.push(c);
+    }
+
+    pub fn push_nested(&mut self, t: Doc<'a>) {
+        self.nested.push(t);
+    }
+
+    // Is there anything worth showing in here, even if the type itself isn't documented?
+    pub fn has_docs(&self) -> bool {
+        !self.fields.is_empty()
+            || self.constants.iter().any(|c| c.is_documented())
+            || self.nested.iter().any(|n| n.is_documented() || n.def.class().map(|c| c.has_docs()).unwrap_or(false))
+    }
+}
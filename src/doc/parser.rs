@@ -0,0 +1,689 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fmt;
+
+use super::lexer::{self, Token, TokenKind};
+use super::{Bound, ClassDef, ConstantDef, Definition, FieldDef, MethodDef, TypeRef, Variable};
+
+// Words that can come before a declaration
+// `non-sealed` is three tokens and is handled on its own
+static MODIFIERS: &[&str] = &[
+    "public", "protected", "private", "static", "final", "abstract", "native",
+    "synchronized", "transient", "volatile", "strictfp", "default", "sealed",
+];
+
+// Words that can never be the name of anything
+static RESERVED: &[&str] = &[
+    "abstract", "assert", "break", "case", "catch", "class", "const", "continue", "default",
+    "do", "else", "enum", "extends", "final", "finally", "for", "goto", "if", "implements",
+    "import", "instanceof", "interface", "native", "new", "package", "private", "protected",
+    "public", "return", "static", "strictfp", "super", "switch", "synchronized", "throw",
+    "throws", "transient", "try", "volatile", "while", "true", "false", "null",
+];
+
+// Types that are also keywords, so they can't name anything either
+static PRIMITIVES: &[&str] = &["void", "boolean", "byte", "char", "short", "int", "long", "float", "double"];
+
+// Something that couldn't be lexed or parsed, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // 1-based line and column, relative to the start of the text that was parsed
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, col: usize, message: &str) -> Self {
+        ParseError {
+            line,
+            col,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+// Parse a single declaration
+// Everything after the `{`, `;`, `=` or `,` that ends the declaration is ignored
+pub fn parse<'a>(src: &str) -> Result<Definition<'a>> {
+    parse_tokens(src, &lexer::lex(src)?)
+}
+
+// Parse a declaration that has already been lexed
+// `tokens` can be any run of the tokens lexed from `src`, and errors point into `src`
+pub fn parse_tokens<'a>(src: &str, tokens: &[Token]) -> Result<Definition<'a>> {
+    let mut parser = Parser { src, tokens, pos: 0, record: false };
+    let def = parser.declaration()?;
+    parser.finish()?;
+    Ok(def)
+}
+
+// The same as parse_tokens(), for a declaration inside the body of a record, where a compact
+// constructor like `Point {` can be
+pub fn parse_record_member<'a>(src: &str, tokens: &[Token]) -> Result<Definition<'a>> {
+    let mut parser = Parser { src, tokens, pos: 0, record: true };
+    let def = parser.declaration()?;
+    parser.finish()?;
+    Ok(def)
+}
+
+// Parse a single enum constant, up to the `,`, `;` or `}` after it
+pub fn parse_constant(src: &str, tokens: &[Token]) -> Result<ConstantDef> {
+    let mut parser = Parser { src, tokens, pos: 0, record: false };
+    let constant = parser.constant()?;
+    match parser.peek() {
+        None => Ok(constant),
+        Some(t) if t.is(",") || t.is(";") || t.is("}") => Ok(constant),
+        Some(_) => Err(parser.unexpected("`,` or `;`")),
+    }
+}
+
+// A recursive descent parser over the tokens of one declaration
+struct Parser<'t> {
+    src: &'t str,
+    tokens: &'t [Token],
+    pos: usize,
+    // Inside the body of a record, so compact constructors are allowed
+    record: bool,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<&'t Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&'t Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    // Is the next token the punctuation `p`?
+    fn at(&self, p: &str) -> bool {
+        self.peek().map(|t| t.is(p)).unwrap_or(false)
+    }
+
+    // Is the next token the word `w`?
+    fn at_word(&self, w: &str) -> bool {
+        self.peek().map(|t| t.is_word(w)).unwrap_or(false)
+    }
+
+    fn eat(&mut self, p: &str) -> bool {
+        let found = self.at(p);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, w: &str) -> bool {
+        let found = self.at_word(w);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, p: &str) -> Result<()> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", p)))
+        }
+    }
+
+    // An error about the next token not being what we wanted
+    fn unexpected(&self, wanted: &str) -> ParseError {
+        match self.peek() {
+            Some(t) => ParseError::new(t.line, t.col, &format!("expected {}, found `{}`", wanted, t.text)),
+            None => self.error_at_end(&format!("expected {}, found the end of the declaration", wanted)),
+        }
+    }
+
+    fn error_at_end(&self, message: &str) -> ParseError {
+        let (line, col) = match self.tokens.last() {
+            Some(t) => (t.line, t.col + t.text.chars().count()),
+            None => (1, 1),
+        };
+        ParseError::new(line, col, message)
+    }
+
+    // The source text from token `start` up to the last token we consumed
+    fn raw(&self, start: usize) -> String {
+        match (self.tokens.get(start), self.tokens.get(self.pos.saturating_sub(1))) {
+            (Some(a), Some(b)) if self.pos > start => self.src[a.start..b.end].to_owned(),
+            _ => String::new(),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(t) if t.kind == TokenKind::Ident && !RESERVED.contains(&t.text.as_str()) => {
+                self.pos += 1;
+                Ok(t.text.clone())
+            },
+            _ => Err(self.unexpected("an identifier")),
+        }
+    }
+
+    // The name of something being declared, which unlike a type can't be `int` or `void`
+    fn name(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(t) if t.kind == TokenKind::Ident && PRIMITIVES.contains(&t.text.as_str()) => Err(self.unexpected("an identifier")),
+            _ => self.ident(),
+        }
+    }
+
+    // Make sure the declaration ends where we think it does
+    fn finish(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(t) if t.is("{") || t.is(";") || t.is("=") || t.is(",") => Ok(()),
+            Some(_) => Err(self.unexpected("`{`, `;` or `=`")),
+        }
+    }
+
+    // declaration := (annotation | modifier)* (type | member)
+    fn declaration<'a>(&mut self) -> Result<Definition<'a>> {
+        let start = self.pos;
+        let (annotations, modifiers) = self.modifiers()?;
+
+        if self.at_type() {
+            return self.type_decl(start, annotations, modifiers);
+        }
+
+        let type_params = if self.at("<") { self.type_params()? } else { Vec::new() };
+
+        // A record's compact canonical constructor is just its name, before the body
+        let is_compact = self.record
+            && type_params.is_empty()
+            && matches!(self.peek(), Some(t) if t.kind == TokenKind::Ident)
+            && self.peek_at(1).map(|t| t.is("{")).unwrap_or(true);
+        if is_compact {
+            let name = self.ident()?;
+            return Ok(Definition::Method(MethodDef {
+                name,
+                modifiers,
+                annotations,
+                type_params,
+                ret: None,
+                args: Vec::new(),
+                throws: Vec::new(),
+                default: None,
+                raw: self.raw(start),
+            }));
+        }
+
+        // A constructor has no return type, just its name and then the parameters
+        let is_constructor = matches!(self.peek(), Some(t) if t.kind == TokenKind::Ident && !PRIMITIVES.contains(&t.text.as_str()))
+            && matches!(self.peek_at(1), Some(t) if t.is("("));
+        let ret = if is_constructor { None } else { Some(self.ty()?) };
+        let name = self.name()?;
+
+        if self.at("(") || ret.is_none() {
+            let args = self.params()?;
+            // Old style `int foo()[]`
+            let mut ret = ret;
+            while self.at("[") && matches!(self.peek_at(1), Some(t) if t.is("]")) {
+                self.pos += 2;
+                if let Some(r) = ret.as_mut() {
+                    r.dims += 1;
+                }
+            }
+
+            let mut throws = Vec::new();
+            if self.eat_word("throws") {
+                throws = self.type_list(",")?;
+            }
+            let raw = self.raw(start);
+
+            // Annotation type elements can have a default value
+            let mut default = None;
+            if self.eat_word("default") {
+                let from = self.pos;
+                while self.peek().map(|t| !t.is(";")).unwrap_or(false) {
+                    self.pos += 1;
+                }
+                default = Some(self.raw(from));
+            }
+
+            return Ok(Definition::Method(MethodDef {
+                name,
+                modifiers,
+                annotations,
+                type_params,
+                ret,
+                args,
+                throws,
+                default,
+                raw,
+            }));
+        }
+
+        // A field, possibly with C style `int ports[]`
+        let mut ty = ret.unwrap();
+        ty.dims += self.dims();
+
+        Ok(Definition::Field(FieldDef {
+            name,
+            modifiers,
+            annotations,
+            ty,
+            value: None,
+            raw: self.raw(start),
+        }))
+    }
+
+    // Annotations and modifiers in front of a declaration, in the order they were written
+    fn modifiers(&mut self) -> Result<(Vec<String>, Vec<String>)> {
+        let mut annotations = Vec::new();
+        let mut modifiers = Vec::new();
+
+        loop {
+            if self.at("@") && !matches!(self.peek_at(1), Some(t) if t.is_word("interface")) {
+                annotations.push(self.annotation()?);
+            } else if self.at_word("non")
+                && matches!(self.peek_at(1), Some(t) if t.is("-"))
+                && matches!(self.peek_at(2), Some(t) if t.is_word("sealed")) {
+                self.pos += 3;
+                modifiers.push(String::from("non-sealed"));
+            } else if let Some(m) = self.peek().filter(|t| t.kind == TokenKind::Ident && MODIFIERS.contains(&t.text.as_str())) {
+                self.pos += 1;
+                modifiers.push(m.text.clone());
+            } else {
+                return Ok((annotations, modifiers));
+            }
+        }
+    }
+
+    // annotation := `@` name (`.` name)* (`(` ... `)`)?
+    fn annotation(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.expect("@")?;
+        self.ident()?;
+        while self.at(".") {
+            self.pos += 1;
+            self.ident()?;
+        }
+        if self.at("(") {
+            self.skip_balanced("(", ")")?;
+        }
+        Ok(self.raw(start))
+    }
+
+    // Skip everything up to and including the matching close bracket
+    fn skip_balanced(&mut self, open: &str, close: &str) -> Result<()> {
+        let mut depth = 0usize;
+        while let Some(t) = self.peek() {
+            self.pos += 1;
+            if t.is(open) {
+                depth += 1;
+            } else if t.is(close) {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        Err(self.error_at_end(&format!("unclosed `{}`", open)))
+    }
+
+    // Annotations can go on types too, but they don't change anything we show
+    fn skip_annotations(&mut self) -> Result<()> {
+        while self.at("@") {
+            self.annotation()?;
+        }
+        Ok(())
+    }
+
+    // type := (`class` | `interface` | `enum` | `record` | `@interface`) name type_params? record_header?
+    //         (`extends` types)? (`implements` types)? (`permits` types)?
+    fn type_decl<'a>(&mut self, start: usize, annotations: Vec<String>, modifiers: Vec<String>) -> Result<Definition<'a>> {
+        if self.eat("@") {
+            self.pos += 1;
+            return self.type_body(start, "@interface", annotations, modifiers);
+        }
+        let kind = self.peek().map(|t| t.text.as_str()).unwrap_or("");
+        self.pos += 1;
+        self.type_body(start, kind, annotations, modifiers)
+    }
+
+    // Is the next thing the start of a type declaration?
+    fn at_type(&self) -> bool {
+        match self.peek() {
+            Some(t) if t.is_word("class") || t.is_word("interface") || t.is_word("enum") => true,
+            // `record` is only a keyword when it's followed by a name
+            Some(t) if t.is_word("record") => matches!(self.peek_at(1), Some(n) if n.kind == TokenKind::Ident),
+            Some(t) if t.is("@") => matches!(self.peek_at(1), Some(n) if n.is_word("interface")),
+            _ => false,
+        }
+    }
+
+    // Everything in a type declaration after the keyword
+    fn type_body<'a>(&mut self, start: usize, kind: &str, annotations: Vec<String>, modifiers: Vec<String>) -> Result<Definition<'a>> {
+        let name = self.name()?;
+        let type_params = if self.at("<") { self.type_params()? } else { Vec::new() };
+        let components = if kind == "record" { self.params()? } else { Vec::new() };
+
+        let mut extends = Vec::new();
+        let mut implements = Vec::new();
+        loop {
+            // Enums and records can't extend anything
+            if kind != "enum" && kind != "record" && self.eat_word("extends") {
+                extends = self.type_list(",")?;
+            } else if self.eat_word("implements") {
+                implements = self.type_list(",")?;
+            } else if self.eat_word("permits") {
+                self.type_list(",")?;
+            } else {
+                break;
+            }
+        }
+
+        let mut class = ClassDef::new(name, self.raw(start));
+        class.annotations = annotations;
+        class.modifiers = modifiers;
+        class.type_params = type_params;
+        class.extends = extends;
+        class.implements = implements;
+        class.components = components;
+
+        Ok(match kind {
+            "class" => Definition::Class(class),
+            "interface" => Definition::Interface(class),
+            "enum" => Definition::Enum(class),
+            "record" => Definition::Record(class),
+            _ => Definition::Annotation(class),
+        })
+    }
+
+    // constant := annotation* name (`(` ... `)`)? (`{` ... `}`)?
+    fn constant(&mut self) -> Result<ConstantDef> {
+        let start = self.pos;
+        let (annotations, _) = self.modifiers()?;
+        let name = self.name()?;
+
+        let mut args = None;
+        if self.at("(") {
+            let from = self.pos;
+            self.skip_balanced("(", ")")?;
+            args = Some(self.raw(from));
+        }
+        let raw = self.raw(start);
+
+        // The constant's own class body isn't documented
+        if self.at("{") {
+            self.skip_balanced("{", "}")?;
+        }
+
+        Ok(ConstantDef {
+            name,
+            annotations,
+            args,
+            raw,
+        })
+    }
+
+    // One or more types separated by `sep`
+    fn type_list(&mut self, sep: &str) -> Result<Vec<TypeRef>> {
+        let mut types = vec![self.ty()?];
+        while self.eat(sep) {
+            types.push(self.ty()?);
+        }
+        Ok(types)
+    }
+
+    // As many `[]` as there are
+    fn dims(&mut self) -> usize {
+        let mut dims = 0;
+        while self.at("[") && matches!(self.peek_at(1), Some(t) if t.is("]")) {
+            self.pos += 2;
+            dims += 1;
+        }
+        dims
+    }
+
+    // type := `?` bound? | name type_args? (`.` name type_args?)* `[]`*
+    fn ty(&mut self) -> Result<TypeRef> {
+        self.skip_annotations()?;
+
+        if self.eat("?") {
+            let mut wildcard = TypeRef::new(String::from("?"));
+            if self.eat_word("extends") {
+                wildcard.bound = Some(Bound::Extends(vec![self.ty()?]));
+            } else if self.eat_word("super") {
+                wildcard.bound = Some(Bound::Super(Box::new(self.ty()?)));
+            }
+            return Ok(wildcard);
+        }
+
+        let mut ty = TypeRef::new(self.ident()?);
+        loop {
+            if self.at("<") {
+                ty.args = self.type_args()?;
+            }
+            if self.at(".") && matches!(self.peek_at(1), Some(t) if t.kind == TokenKind::Ident || t.is("@")) {
+                self.pos += 1;
+                self.skip_annotations()?;
+                ty.name.push('.');
+                ty.name += &self.ident()?;
+            } else {
+                break;
+            }
+        }
+
+        self.skip_annotations()?;
+        ty.dims = self.dims();
+        Ok(ty)
+    }
+
+    // type_args := `<` (type (`,` type)*)? `>`
+    fn type_args(&mut self) -> Result<Vec<TypeRef>> {
+        self.expect("<")?;
+        // The diamond, `new Foo<>()`
+        if self.eat(">") {
+            return Ok(Vec::new());
+        }
+        let args = self.type_list(",")?;
+        self.expect(">")?;
+        Ok(args)
+    }
+
+    // type_params := `<` name (`extends` type (`&` type)*)? (`,` ...)* `>`
+    fn type_params(&mut self) -> Result<Vec<TypeRef>> {
+        self.expect("<")?;
+        let mut params = Vec::new();
+        loop {
+            self.skip_annotations()?;
+            let mut param = TypeRef::new(self.ident()?);
+            if self.eat_word("extends") {
+                param.bound = Some(Bound::Extends(self.type_list("&")?));
+            }
+            params.push(param);
+
+            if !self.eat(",") {
+                self.expect(">")?;
+                return Ok(params);
+            }
+        }
+    }
+
+    // params := `(` (param (`,` param)*)? `)`
+    // param := (annotation | `final`)* type `...`? name `[]`*
+    fn params(&mut self) -> Result<Vec<Variable>> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        if self.eat(")") {
+            return Ok(params);
+        }
+
+        loop {
+            self.modifiers()?;
+            let mut ty = self.ty()?;
+            let varargs = self.eat("...");
+            // `this` is allowed as an explicit receiver parameter
+            let name = if self.at_word("this") {
+                self.pos += 1;
+                String::from("this")
+            } else {
+                self.name()?
+            };
+            ty.dims += self.dims();
+            params.push(Variable::new(name, ty, varargs));
+
+            // Only the last parameter can be varargs
+            if varargs || !self.eat(",") {
+                self.expect(")")?;
+                return Ok(params);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(def: Definition) -> MethodDef {
+        match def {
+            Definition::Method(m) => m,
+            d => panic!("expected a method, found {}", d.kind()),
+        }
+    }
+
+    fn field(def: Definition) -> FieldDef {
+        match def {
+            Definition::Field(f) => f,
+            d => panic!("expected a field, found {}", d.kind()),
+        }
+    }
+
+    fn class(def: Definition) -> ClassDef {
+        match def.class() {
+            Some(c) => c.clone(),
+            None => panic!("expected a type, found {}", def.kind()),
+        }
+    }
+
+    #[test]
+    fn generics() {
+        let m = method(parse("public static <T extends Comparable<? super T>> List<T> sort(Map<String, List<T>>[] maps) {").unwrap());
+        assert_eq!(m.name, "sort");
+        assert_eq!(m.modifiers, vec!["public", "static"]);
+        assert_eq!(m.type_params.len(), 1);
+        assert_eq!(m.type_params[0].to_string(), "T extends Comparable<? super T>");
+        assert_eq!(m.ret.unwrap().to_string(), "List<T>");
+
+        let ty = &m.args[0].ty;
+        assert_eq!(ty.name, "Map");
+        assert_eq!(ty.dims, 1);
+        assert_eq!(ty.args.len(), 2);
+        assert_eq!(ty.args[1].to_string(), "List<T>");
+        assert_eq!(ty.to_string(), "Map<String, List<T>>[]");
+
+        let c = class(parse("public abstract class Cache<K, V extends Comparable<V>> extends Base<K> implements Map<K, V>, AutoCloseable {").unwrap());
+        assert_eq!(c.get_name(), "Cache");
+        assert_eq!(c.type_params.iter().map(|t| t.to_string()).collect::<Vec<String>>(), vec!["K", "V extends Comparable<V>"]);
+        assert_eq!(c.extends[0].to_string(), "Base<K>");
+        assert_eq!(c.implements.iter().map(|t| t.to_string()).collect::<Vec<String>>(), vec!["Map<K, V>", "AutoCloseable"]);
+    }
+
+    #[test]
+    fn varargs() {
+        let m = method(parse("void log(final String format, Object... args);").unwrap());
+        assert_eq!(m.ret.unwrap().name, "void");
+        assert_eq!(m.args.len(), 2);
+        assert!(!m.args[0].varargs);
+        assert!(m.args[1].varargs);
+        assert_eq!(m.args[1].to_string(), "Object... args");
+
+        // Only the last parameter can be varargs
+        assert!(parse("void log(Object... args, String format);").is_err());
+    }
+
+    #[test]
+    fn throws() {
+        let m = method(parse("public Robot(String name) throws IOException, java.sql.SQLException {").unwrap());
+        assert!(m.is_constructor());
+        assert_eq!(m.throws.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>(), vec!["IOException", "java.sql.SQLException"]);
+        assert_eq!(m.throws[1].simple_name(), "SQLException");
+
+        assert!(parse("void open() throws {").is_err());
+    }
+
+    #[test]
+    fn fields() {
+        let f = field(parse("@Deprecated private static final int[] PORTS = {1, 2};").unwrap());
+        assert_eq!(f.name, "PORTS");
+        assert_eq!(f.annotations, vec!["@Deprecated"]);
+        assert_eq!(f.modifiers, vec!["private", "static", "final"]);
+        assert_eq!(f.ty.to_string(), "int[]");
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(parse("public interface Drivable<T> extends Subsystem {").unwrap().kind(), "Interface");
+        assert_eq!(parse("enum Color implements Named {").unwrap().kind(), "Enum");
+        assert_eq!(parse("public @interface Command {").unwrap().kind(), "Annotation Type");
+        assert_eq!(parse("public static final class Inner {").unwrap().kind(), "Class");
+
+        let m = method(parse("String value() default \"none\";").unwrap());
+        assert_eq!(m.default.as_deref(), Some("\"none\""));
+    }
+
+    #[test]
+    fn records() {
+        let r = parse("public record Point<T>(int x, @Nullable T y, String... tags) implements Shape {").unwrap();
+        assert_eq!(r.kind(), "Record");
+        let c = class(r);
+        assert_eq!(c.get_name(), "Point");
+        assert_eq!(c.components.iter().map(|v| v.to_string()).collect::<Vec<String>>(), vec!["int x", "T y", "String... tags"]);
+        assert_eq!(c.implements[0].name, "Shape");
+
+        let c = class(parse("record Empty() {").unwrap());
+        assert!(c.components.is_empty());
+
+        // Records can't extend anything
+        assert!(parse("record Point(int x) extends Shape {").is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        let err = parse("public void (int a) {").unwrap_err();
+        assert_eq!((err.line, err.col), (1, 13));
+
+        let err = parse("public int add(int a,\n               int b {").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(parse("class {").is_err());
+        assert!(parse("public static").is_err());
+        assert!(parse("int x = \"unterminated;").is_err());
+        assert!(parse("List<String names;").is_err());
+    }
+
+    #[test]
+    fn compact_constructors() {
+        let src = "public Point {";
+        let m = method(parse_record_member(src, &lexer::lex(src).unwrap()).unwrap());
+        assert_eq!(m.name, "Point");
+        assert!(m.is_constructor());
+        assert!(m.args.is_empty());
+        assert_eq!(m.modifiers, vec!["public"]);
+        assert_eq!(m.raw, "public Point");
+
+        // Only records have them
+        assert!(parse(src).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::comment;
+use super::{Definition, Doc, Package};
+
+// What a reference like `Foo#bar(int)` points at
+#[derive(Debug, Clone, Copy)]
+pub struct Target<'a> {
+    pub ty: &'a Doc<'a>,
+    // The field, method or constant inside the type, if it's a reference to one
+    pub member: Option<&'a Doc<'a>>,
+}
+
+// A reference in a comment that doesn't point at anything we know about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unresolved {
+    pub file: PathBuf,
+    pub line: usize,
+    pub reference: String,
+}
+
+// Every documented type in a project, for looking references up in
+// Names are looked up in maps so big projects don't have to search every type for every link,
+// and where two types share a name the first one listed wins, like it would searching in order
+pub struct Index<'a> {
+    types: Vec<&'a Doc<'a>>,
+    by_full_name: HashMap<String, &'a Doc<'a>>,
+    // By package and then name inside it, like `Outer.Inner`
+    by_qualified_name: HashMap<(String, String), &'a Doc<'a>>,
+    // By name inside the package, in any package
+    by_name: HashMap<String, &'a Doc<'a>>,
+    // What each file imports, for telling references to other libraries from mistakes
+    imports: HashMap<&'a Path, &'a [String]>,
+    packages: HashSet<&'a str>,
+}
+
+// The types in `java.lang`, which every file can use without importing them
+static JAVA_LANG: &[&str] = &[
+    "Appendable", "ArithmeticException", "ArrayIndexOutOfBoundsException", "ArrayStoreException",
+    "AssertionError", "AutoCloseable", "Boolean", "Byte", "CharSequence", "Character", "Class",
+    "ClassCastException", "ClassLoader", "ClassNotFoundException", "CloneNotSupportedException",
+    "Cloneable", "Comparable", "Deprecated", "Double", "Enum", "Error", "Exception",
+    "ExceptionInInitializerError", "Float", "FunctionalInterface", "IllegalAccessException",
+    "IllegalArgumentException", "IllegalStateException", "IndexOutOfBoundsException",
+    "InheritableThreadLocal", "InstantiationException", "Integer", "InterruptedException",
+    "Iterable", "LinkageError", "Long", "Math", "NegativeArraySizeException",
+    "NoSuchFieldException", "NoSuchMethodException", "NullPointerException", "Number",
+    "NumberFormatException", "Object", "OutOfMemoryError", "Override", "Process",
+    "ProcessBuilder", "Readable", "Record", "ReflectiveOperationException", "Runnable", "Runtime",
+    "RuntimeException", "SafeVarargs", "SecurityException", "Short", "StackOverflowError",
+    "StrictMath", "String", "StringBuffer", "StringBuilder", "StringIndexOutOfBoundsException",
+    "SuppressWarnings", "System", "Thread", "ThreadLocal", "Throwable",
+    "UnsupportedOperationException", "VirtualMachineError", "Void",
+];
+
+impl<'a> Index<'a> {
+    pub fn new(packages: &'a [Package<'a>]) -> Self {
+        let types = packages.iter().flat_map(|p| p.all_types()).collect::<Vec<&'a Doc<'a>>>();
+        let mut by_full_name = HashMap::new();
+        let mut by_qualified_name = HashMap::new();
+        let mut by_name = HashMap::new();
+        let mut imports = HashMap::new();
+        for t in &types {
+            if t.scope.is_empty() {
+                imports.entry(t.file.as_path()).or_insert(t.imports.as_slice());
+            }
+            let qualified = t.qualified_name();
+            by_full_name.entry(t.full_name()).or_insert(*t);
+            by_qualified_name.entry((t.pkg.clone(), qualified.clone())).or_insert(*t);
+            by_name.entry(qualified).or_insert(*t);
+        }
+        Index {
+            types,
+            by_full_name,
+            by_qualified_name,
+            by_name,
+            imports,
+            packages: packages.iter().map(|p| p.name.as_str()).collect(),
+        }
+    }
+
+    // Is a reference to something outside the project, that we can't check and don't complain about?
+    // That's anything in the JDK, `java.lang` types like `String`, and types the file imports
+    // from packages that aren't part of the project
+    pub fn is_external(&self, reference: &str, context: Option<&'a Doc<'a>>) -> bool {
+        let ty = reference.split('#').next().unwrap_or(reference);
+        let ty = ty.split('<').next().unwrap_or(ty);
+        if ty.is_empty() {
+            return false;
+        }
+        if ty.starts_with("java.") || ty.starts_with("javax.") {
+            return true;
+        }
+        if self.find_type(ty, context).is_some() {
+            return false;
+        }
+
+        // `Map.Entry` is found by its outermost type
+        let first = ty.split('.').next().unwrap_or(ty);
+        if JAVA_LANG.contains(&first) {
+            return true;
+        }
+
+        let imports = context.and_then(|c| self.imports.get(c.file.as_path())).copied().unwrap_or_default();
+        imports.iter().any(|i| match i.strip_suffix(".*") {
+            Some(pkg) => !self.packages.contains(pkg),
+            None => i == ty || i.rsplit('.').next() == Some(first),
+        })
+    }
+
+    // Look a reference up from a comment in or on `context`
+    // Types are found by their full name, as a type nested in `context` or one it's nested in,
+    // in the same package, and then anywhere in the project by name
+    pub fn resolve(&self, reference: &str, context: Option<&'a Doc<'a>>) -> Option<Target<'a>> {
+        let (ty, member) = match reference.find('#') {
+            Some(i) => (&reference[..i], Some(&reference[i + 1..])),
+            None => (reference, None),
+        };
+
+        let ty = if ty.is_empty() {
+            context?
+        } else {
+            self.find_type(ty, context)?
+        };
+
+        match member {
+            Some(m) => find_member(ty, m).map(|m| Target { ty, member: Some(m) }),
+            None => Some(Target { ty, member: None }),
+        }
+    }
+
+    fn find_type(&self, name: &str, context: Option<&'a Doc<'a>>) -> Option<&'a Doc<'a>> {
+        // Generics don't matter for finding a type
+        let name = name.split('<').next().unwrap_or(name);
+
+        if let Some(t) = self.by_full_name.get(name) {
+            return Some(t);
+        }
+
+        if let Some(c) = context {
+            // From the inside out, `Inner` inside `Outer.Middle` could be any of
+            // `Outer.Middle.Inner`, `Outer.Inner` or just `Inner`
+            let mut scope = c.scope.clone();
+            scope.push(c.name().to_owned());
+            loop {
+                let mut qualified = scope.join(".");
+                if !qualified.is_empty() {
+                    qualified.push('.');
+                }
+                qualified += name;
+
+                if let Some(t) = self.by_qualified_name.get(&(c.pkg.clone(), qualified)) {
+                    return Some(t);
+                }
+                if scope.pop().is_none() {
+                    break;
+                }
+            }
+        }
+
+        // Imports aren't followed, so take the first type that fits anywhere
+        self.by_name.get(name).copied()
+    }
+
+    // Every reference in every comment that we couldn't find, in the order they're written
+    pub fn unresolved(&self) -> Vec<Unresolved> {
+        let mut out = Vec::new();
+        for t in &self.types {
+            self.check(t, t, &mut out);
+            if let Some(c) = t.def.class() {
+                for m in c.constants.iter().chain(c.fields.iter()) {
+                    self.check(m, t, &mut out);
+                }
+            }
+        }
+        out
+    }
+
+    fn check(&self, doc: &'a Doc<'a>, context: &'a Doc<'a>, out: &mut Vec<Unresolved>) {
+        for (n, reference) in comment::references(&doc.tag) {
+            if self.is_external(&reference, Some(context)) || self.resolve(&reference, Some(context)).is_some() {
+                continue;
+            }
+            out.push(Unresolved {
+                file: doc.file.clone(),
+                line: doc.line + n,
+                reference,
+            });
+        }
+    }
+}
+
+// A field, method, constructor or enum constant inside a type
+// `name(int, String)` picks one overload by its parameter types, and just `name` is the first one
+fn find_member<'a>(ty: &'a Doc<'a>, member: &str) -> Option<&'a Doc<'a>> {
+    let class = ty.def.class()?;
+    let (name, params) = match member.find('(') {
+        Some(i) => {
+            let params = member[i + 1..].trim_end_matches(')');
+            let params = split_params(params)
+                .into_iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                // `Foo#bar(int count)` is allowed to name the parameter too
+                .map(|p| match p.rfind(|c: char| c.is_whitespace()) {
+                    Some(i) if !p[i..].contains('>') => p[..i].trim_end(),
+                    _ => p,
+                })
+                .collect::<Vec<&str>>();
+            (&member[..i], Some(params))
+        },
+        None => (member, None),
+    };
+
+    class.constants.iter().chain(class.fields.iter()).find(|m| {
+        if m.name() != name {
+            return false;
+        }
+        match (&m.def, &params) {
+            (Definition::Method(def), Some(params)) => {
+                def.args.len() == params.len() && def.args.iter().zip(params).all(|(a, p)| {
+                    let mut ty = a.ty.simple_name().to_owned() + &"[]".repeat(a.ty.dims);
+                    if a.varargs {
+                        ty += "...";
+                    }
+                    same_type(&ty, p)
+                })
+            },
+            _ => true,
+        }
+    })
+}
+
+// The parameters in a reference, split at the commas that aren't inside generics
+fn split_params(params: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                out.push(&params[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    out.push(&params[start..]);
+    out
+}
+
+// Does a parameter type match one written in a reference?
+// The reference can use a qualified name, leave out generics and write `int...` as `int[]`
+fn same_type(declared: &str, written: &str) -> bool {
+    let simple = |t: &str| {
+        let base = t.split('<').next().unwrap_or(t);
+        let dims = t.matches("[]").count() + t.matches("...").count();
+        let name = base.trim_end_matches("...").trim_end_matches("[]");
+        (name.rsplit('.').next().unwrap_or(name).to_owned(), dims)
+    };
+    simple(declared) == simple(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::scan::scan_source;
+
+    fn unresolved(files: &[(&str, &str)]) -> Vec<String> {
+        let docs = files.iter().flat_map(|(path, src)| scan_source(Path::new(path), src).docs).collect();
+        let packages = Package::group(docs, Vec::new());
+        Index::new(&packages).unresolved().into_iter().map(|u| u.reference).collect()
+    }
+
+    #[test]
+    fn outside_references_are_external() {
+        let src = "package frc.robot;\n\
+                   import java.util.List;\n\
+                   import static org.junit.Assert.assertEquals;\n\
+                   /**\n\
+                   \x20* Uses {@link String}, {@link Object#equals}, {@link List},\n\
+                   \x20* {@link Assert#assertEquals} and {@link Map.Entry}.\n\
+                   \x20*/\n\
+                   public class Robot {}\n";
+        assert_eq!(unresolved(&[("Robot.java", src)]), vec!["Map.Entry"]);
+
+        // Anything could come from another library's package
+        let src = "package frc.robot;\n\
+                   import edu.wpi.first.wpilibj.*;\n\
+                   /** A {@link TimedRobot}. */\n\
+                   public class Robot {}\n";
+        assert!(unresolved(&[("Robot.java", src)]).is_empty());
+    }
+
+    #[test]
+    fn wildcards_of_project_packages_are_checked() {
+        let robot = "package frc.robot;\n\
+                     import frc.robot.subsystems.*;\n\
+                     /** Drives with {@link Drive} and {@link Missing}. */\n\
+                     public class Robot {}\n";
+        let drive = "package frc.robot.subsystems;\n\
+                     /** Drives. */\n\
+                     public class Drive {}\n";
+        assert_eq!(unresolved(&[("Robot.java", robot), ("Drive.java", drive)]), vec!["Missing"]);
+    }
+
+    #[test]
+    fn imports_are_per_file() {
+        let a = "package p;\nimport java.util.List;\n/** A. */\npublic class A {}\n";
+        let b = "package p;\n/** See {@link List}. */\npublic class B {}\n";
+        assert_eq!(unresolved(&[("A.java", a), ("B.java", b)]), vec!["List"]);
+    }
+
+    #[test]
+    fn generic_parameters() {
+        let src = "package p;\n\
+                   /** See {@link #put(Map<String, Integer>)} and {@link #put(Map<String, Integer> values, int)}. */\n\
+                   public class Cache {\n\
+                   \x20   /** Puts. */\n\
+                   \x20   public void put(Map<String, Integer> values) {}\n\
+                   \x20   /** Puts some. */\n\
+                   \x20   public void put(Map<String, Integer> values, int count) {}\n\
+                   }\n";
+        assert!(unresolved(&[("Cache.java", src)]).is_empty());
+    }
+}
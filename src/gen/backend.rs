@@ -0,0 +1,88 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use super::{Model, Page};
+
+/// Something that turns the documentation into one kind of output, like html or JSON.
+///
+/// Backends don't touch the disk themselves, they hand every file they make to a [`Sink`], so the
+/// same backend can write to a directory, into memory, or anywhere else.
+///
+/// ```
+/// use std::io;
+/// use bach::gen::{Model, OutputBackend, Sink};
+///
+/// // A list of every type, one per line
+/// struct TypeList;
+///
+/// impl OutputBackend for TypeList {
+///     fn name(&self) -> &str {
+///         "type list"
+///     }
+///
+///     fn generate(&self, model: &Model, sink: &mut dyn Sink) -> io::Result<()> {
+///         let names = model.packages.iter()
+///             .flat_map(|p| p.all_types())
+///             .map(|t| t.full_name() + "\n")
+///             .collect::<String>();
+///         sink.write("types.txt", &names)
+///     }
+/// }
+/// ```
+pub trait OutputBackend {
+    /// What it's called in logs, like `"html"`.
+    fn name(&self) -> &str;
+
+    /// Make every file of output for `model`, and write them into `sink`.
+    fn generate(&self, model: &Model, sink: &mut dyn Sink) -> io::Result<()>;
+}
+
+/// Where the files a backend makes go.
+pub trait Sink {
+    /// Write one file, with its path relative to the top of the output, like `frc/robot/Robot.html`.
+    fn write(&mut self, path: &str, content: &str) -> io::Result<()>;
+}
+
+/// Writes into a directory on disk, making any directories a path needs.
+#[derive(Debug, Clone)]
+pub struct DirSink {
+    root: PathBuf,
+}
+
+impl DirSink {
+    /// Write everything under `root`, which is made if it isn't there yet.
+    pub fn new(root: PathBuf) -> Self {
+        DirSink { root }
+    }
+}
+
+impl Sink for DirSink {
+    fn write(&mut self, path: &str, content: &str) -> io::Result<()> {
+        let path = self.root.join(path);
+        // Errors don't say which file they're about, so add it
+        let with_path = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(with_path)?;
+        }
+        fs::write(&path, content).map_err(with_path)
+    }
+}
+
+/// Keeps every file in memory, in the order they were written.
+impl Sink for Vec<Page> {
+    fn write(&mut self, path: &str, content: &str) -> io::Result<()> {
+        self.push(Page {
+            path: path.to_owned(),
+            content: content.to_owned(),
+        });
+        Ok(())
+    }
+}
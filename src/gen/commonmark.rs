@@ -0,0 +1,158 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::convert::TryFrom;
+
+use pulldown_cmark::{html, CowStr, Event, HeadingLevel, Options, Parser, Tag};
+
+use crate::doc::comment::{self, Inline};
+
+use super::escape::{self, Html};
+
+// How far headings in comments are pushed down, so `#` in a comment comes out as an <h4>
+// and never fights with the headings of the page it's on
+static HEADING_OFFSET: usize = 3;
+
+// Inline tags are swapped out for these while the Markdown is rendered, then swapped back in
+static OPEN: char = '\u{E000}';
+static CLOSE: char = '\u{E001}';
+
+// Render a doc comment's Markdown to html
+// `tag` renders an inline tag like `{@link Foo}`, which is done before the Markdown so the
+// two can't trip over each other
+// Any html in the comment is cleaned up by escape::Sanitizer
+pub fn render(text: &str, tag: impl Fn(&Inline) -> Html) -> Html {
+    // Every inline tag, as it was written and as html
+    let mut tags = Vec::new();
+    let mut src = String::new();
+    for (span, i) in comment::inline_spans(text) {
+        match i {
+            Inline::Text(t) => src += &t,
+            i => {
+                src += &format!("{}{}{}", OPEN, tags.len(), CLOSE);
+                tags.push((&text[span], tag(&i)));
+            },
+        }
+    }
+
+    let mut in_code = false;
+    let mut sanitizer = escape::Sanitizer::default();
+    let events = Parser::new_ext(&src, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|e| match e {
+        Event::Start(Tag::Heading(level, id, classes)) => Event::Start(Tag::Heading(demote(level), id, classes)),
+        Event::End(Tag::Heading(level, id, classes)) => Event::End(Tag::Heading(demote(level), id, classes)),
+        Event::Start(Tag::CodeBlock(kind)) => {
+            in_code = true;
+            Event::Start(Tag::CodeBlock(kind))
+        },
+        Event::End(Tag::CodeBlock(kind)) => {
+            in_code = false;
+            Event::End(Tag::CodeBlock(kind))
+        },
+        // Code means exactly what it says, so an inline tag in it is put back the way it was written
+        Event::Code(c) => Event::Code(CowStr::from(restore(&c, &tags))),
+        Event::Text(t) if in_code => Event::Text(CowStr::from(restore(&t, &tags))),
+        Event::Html(h) => Event::Html(CowStr::from(sanitizer.sanitize(&h).to_string())),
+        Event::Start(Tag::Link(kind, url, title)) => Event::Start(Tag::Link(kind, safe(url), title)),
+        Event::End(Tag::Link(kind, url, title)) => Event::End(Tag::Link(kind, safe(url), title)),
+        Event::Start(Tag::Image(kind, url, title)) => Event::Start(Tag::Image(kind, safe(url), title)),
+        Event::End(Tag::Image(kind, url, title)) => Event::End(Tag::Image(kind, safe(url), title)),
+        e => e,
+    });
+
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+
+    // Now the rest of the inline tags go back in as html
+    for (n, (_, html)) in tags.iter().enumerate() {
+        out = out.replace(&format!("{}{}{}", OPEN, n, CLOSE), html.as_str());
+    }
+    Html::trusted(out)
+}
+
+// Render a short bit of Markdown, like a summary or a `@param`, without wrapping it in a paragraph
+pub fn render_inline(text: &str, tag: impl Fn(&Inline) -> Html) -> Html {
+    let out = render(text, tag);
+    let trimmed = out.as_str().trim_end();
+    match trimmed.strip_prefix("<p>").and_then(|s| s.strip_suffix("</p>")) {
+        // Only if it really is one paragraph
+        Some(inner) if !inner.contains("<p>") => Html::trusted(inner.to_owned()),
+        _ => out,
+    }
+}
+
+// Rewrite the inline tags in some Markdown with `tag`, and leave everything else alone
+// For writing Markdown back out, like for a wiki. Tags inside code stay the way they were written,
+// the same as in render()
+pub fn rewrite(text: &str, tag: impl Fn(&Inline) -> String) -> String {
+    let code = Parser::new_ext(text, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH)
+        .into_offset_iter()
+        .filter_map(|(e, range)| match e {
+            Event::Code(_) | Event::Start(Tag::CodeBlock(_)) => Some(range),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    for (span, i) in comment::inline_spans(text) {
+        match i {
+            Inline::Text(_) => out += &text[span],
+            _ if code.iter().any(|c| c.contains(&span.start)) => out += &text[span],
+            i => out += &tag(&i),
+        }
+    }
+    out
+}
+
+// Links and images that would run something, like `javascript:`, go nowhere instead
+fn safe(url: CowStr) -> CowStr {
+    if escape::safe_url(&url) {
+        url
+    } else {
+        CowStr::from("#")
+    }
+}
+
+fn demote(level: HeadingLevel) -> HeadingLevel {
+    HeadingLevel::try_from((level as usize + HEADING_OFFSET).min(6)).unwrap_or(HeadingLevel::H6)
+}
+
+// Put inline tags back as they were written
+fn restore(text: &str, tags: &[(&str, Html)]) -> String {
+    let mut text = text.to_owned();
+    for (n, (written, _)) in tags.iter().enumerate() {
+        text = text.replace(&format!("{}{}{}", OPEN, n, CLOSE), written);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Links become `[target]`, and every other inline tag `<tag>`
+    fn rewrite_links(text: &str) -> String {
+        rewrite(text, |i| match i {
+            Inline::Link { target, .. } => format!("[{}]", target),
+            _ => String::from("<tag>"),
+        })
+    }
+
+    #[test]
+    fn rewrites_inline_tags() {
+        assert_eq!(rewrite_links("See {@link Drive#drive(double, double)} and {@linkplain Arm the arm}."),
+                   "See [Drive#drive(double, double)] and [Arm].");
+        assert_eq!(rewrite_links("Is {@value #MAX} or {@code x}.\n\n- {@link Arm}"), "Is <tag> or <tag>.\n\n- [Arm]");
+        // Everything else stays as written
+        assert_eq!(rewrite_links("# Drive\n\n*Fast* [docs](https://wpilib.org) <b>x</b>"), "# Drive\n\n*Fast* [docs](https://wpilib.org) <b>x</b>");
+    }
+
+    #[test]
+    fn leaves_code_alone() {
+        assert_eq!(rewrite_links("`{@link Arm}` and {@link Arm}"), "`{@link Arm}` and [Arm]");
+        assert_eq!(rewrite_links("```java\n/** {@link Arm} */\n```\n{@link Arm}"), "```java\n/** {@link Arm} */\n```\n[Arm]");
+        assert_eq!(rewrite_links("    indented {@link Arm}\n\n{@link Arm}"), "    indented {@link Arm}\n\n[Arm]");
+    }
+}
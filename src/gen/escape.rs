@@ -0,0 +1,228 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fmt;
+
+// Tags that comments are allowed to use as they are, as long as they have no attributes
+// Anything else, like `<script>` or `<img onerror=...>`, is shown as text
+static ALLOWED_TAGS: &[&str] = &[
+    "p", "br", "hr", "b", "i", "u", "em", "strong", "code", "tt", "pre", "kbd", "var", "sub", "sup", "small",
+    "ul", "ol", "li", "dl", "dt", "dd", "blockquote", "table", "thead", "tbody", "tr", "th", "td",
+    "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+// Escape a value so it shows up in a page exactly as written, even inside an attribute
+macro_rules! esc {
+    ( $s:expr ) => {
+        $crate::gen::escape::escape(&$s.to_string())
+    };
+}
+
+// Html that is safe to put in a page as it is
+// The only ways to get one are escaping some text, or being the doc comment renderer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Html(String);
+
+impl Html {
+    // Only for html that bach built itself
+    pub(crate) fn trusted(s: String) -> Self {
+        Html(s)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Html {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub fn escape(s: &str) -> Html {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out += "&amp;",
+            '<' => out += "&lt;",
+            '>' => out += "&gt;",
+            '"' => out += "&quot;",
+            '\'' => out += "&#39;",
+            c => out.push(c),
+        }
+    }
+    Html(out)
+}
+
+// Cleans up html written in a comment, keeping the simple formatting tags and `<a href>`
+// One comment's html can come in pieces, like one tag at a time out of the Markdown parser
+// It remembers which `<a>`s were kept, so a `</a>` is only kept if the link it closes was
+#[derive(Debug, Default)]
+pub struct Sanitizer {
+    links: Vec<bool>,
+}
+
+impl Sanitizer {
+    pub fn sanitize(&mut self, s: &str) -> Html {
+        let mut out = String::new();
+        let mut rest = s;
+        while let Some(start) = rest.find('<') {
+            // A `<` that's never closed is just text, like the rest
+            let tag = match rest[start..].find('>') {
+                Some(end) => &rest[start..=start + end],
+                None => break,
+            };
+            out += escape(&rest[..start]).as_str();
+            match self.allowed(tag) {
+                Some(clean) => out += &clean,
+                None => out += escape(tag).as_str(),
+            }
+            rest = &rest[start + tag.len()..];
+        }
+        out += escape(rest).as_str();
+        Html(out)
+    }
+
+    // An `<a>` or `</a>`, keeping track of which links are open
+    fn allowed(&mut self, tag: &str) -> Option<String> {
+        let clean = allowed(tag);
+        match link(tag) {
+            Some(false) => self.links.push(clean.is_some()),
+            Some(true) if !self.links.pop().unwrap_or(false) => return None,
+            _ => (),
+        }
+        clean
+    }
+}
+
+// Whether a tag is a closing `</a>`, if it's a link at all
+fn link(tag: &str) -> Option<bool> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim();
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(i) => (true, i.trim()),
+        None => (false, inner),
+    };
+    let name = inner.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+    if name.eq_ignore_ascii_case("a") {
+        Some(closing)
+    } else {
+        None
+    }
+}
+
+// The tag as it should be written, if it's allowed
+// A `</a>` always is, and it's up to the Sanitizer to check it closes a link that was kept
+fn allowed(tag: &str) -> Option<String> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/').trim();
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(i) => (true, i.trim()),
+        None => (false, inner),
+    };
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+    let attrs = inner[name_end..].trim();
+
+    if ALLOWED_TAGS.contains(&name.as_str()) && attrs.is_empty() {
+        return Some(if closing { format!("</{}>", name) } else { format!("<{}>", name) });
+    }
+
+    // Links only keep their href, and only if it doesn't run anything
+    if name == "a" {
+        if closing {
+            return Some(String::from("</a>"));
+        }
+        // Anything after the href's value, like an `onclick`, is dropped
+        let value = attrs.strip_prefix("href")?.trim_start().strip_prefix('=')?.trim_start();
+        let href = match value.chars().next() {
+            Some(q) if q == '"' || q == '\'' => &value[1..1 + value[1..].find(q)?],
+            _ => value.split_whitespace().next().unwrap_or(""),
+        };
+        if !safe_url(href) {
+            return None;
+        }
+        return Some(format!("<a href=\"{}\">", escape(href)));
+    }
+    None
+}
+
+// Is this a url we're happy to link to? Relative links, anchors, and http(s) and mailto ones
+pub fn safe_url(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    match url.find(':') {
+        Some(i) => {
+            // A `:` after a `/`, `?` or `#` isn't a scheme
+            let scheme = &url[..i];
+            scheme.contains(['/', '?', '#']) || ["http", "https", "mailto"].contains(&scheme)
+        },
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(s: &str) -> Html {
+        Sanitizer::default().sanitize(s)
+    }
+
+    #[test]
+    fn escapes_entities() {
+        assert_eq!(escape(r#"<a href="x">Tom & Jerry's</a>"#).as_str(), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(esc!(1.5).as_str(), "1.5");
+        // Already escaped text is escaped again, so it shows as written
+        assert_eq!(escape("&lt;").as_str(), "&amp;lt;");
+    }
+
+    #[test]
+    fn keeps_simple_tags() {
+        assert_eq!(sanitize("<P>Some <B>bold</b> text<br/></p>").as_str(), "<p>Some <b>bold</b> text<br></p>");
+        assert_eq!(sanitize("1 < 2 && 3 > 2").as_str(), "1 &lt; 2 &amp;&amp; 3 &gt; 2");
+        assert_eq!(sanitize("List<String>").as_str(), "List&lt;String&gt;");
+        assert_eq!(sanitize("a < b").as_str(), "a &lt; b");
+    }
+
+    #[test]
+    fn strips_scripts() {
+        assert_eq!(sanitize("<script>alert(1)</script>").as_str(), "&lt;script&gt;alert(1)&lt;/script&gt;");
+        assert_eq!(sanitize("<SCRIPT src=x.js></SCRIPT>").as_str(), "&lt;SCRIPT src=x.js&gt;&lt;/SCRIPT&gt;");
+        assert_eq!(sanitize("<style>body{}</style>").as_str(), "&lt;style&gt;body{}&lt;/style&gt;");
+    }
+
+    #[test]
+    fn strips_event_handlers() {
+        assert_eq!(sanitize("<img src=x onerror=alert(1)>").as_str(), "&lt;img src=x onerror=alert(1)&gt;");
+        assert_eq!(sanitize(r#"<b onclick="alert(1)">hi</b>"#).as_str(), "&lt;b onclick=&quot;alert(1)&quot;&gt;hi</b>");
+        assert_eq!(sanitize(r#"<a href="Robot.html" onmouseover="alert(1)">Robot</a>"#).as_str(), r#"<a href="Robot.html">Robot</a>"#);
+        assert_eq!(sanitize("<a href='Robot.html'onclick=alert(1)>").as_str(), r#"<a href="Robot.html">"#);
+        assert_eq!(sanitize(r#"<a onclick="alert(1)" href="Robot.html">"#).as_str(), "&lt;a onclick=&quot;alert(1)&quot; href=&quot;Robot.html&quot;&gt;");
+    }
+
+    #[test]
+    fn javascript_urls() {
+        assert_eq!(sanitize(r#"<a href="javascript:alert(1)">x</a>"#).as_str(), "&lt;a href=&quot;javascript:alert(1)&quot;&gt;x&lt;/a&gt;");
+        assert!(!sanitize(r#"<a href=" JavaScript:alert(1)">"#).as_str().contains("<a"));
+        assert!(!sanitize("<a href=data:text/html,hi>").as_str().contains("<a"));
+        assert!(!sanitize("<a href=vbscript:msgbox>").as_str().contains("<a"));
+        // An escaped colon isn't a scheme, and stays escaped in the href
+        assert_eq!(sanitize("<a href=javascript&#58;alert(1)>").as_str(), r#"<a href="javascript&amp;#58;alert(1)">"#);
+
+        assert_eq!(sanitize(r#"<a href="https://wpilib.org/?a=b&c=d">"#).as_str(), r#"<a href="https://wpilib.org/?a=b&amp;c=d">"#);
+        // Only the `</a>` that closes a kept link is kept
+        assert_eq!(sanitize(r#"<a href="a.html"><a href="javascript:x">b</a>c</a></a>"#).as_str(),
+                   r#"<a href="a.html">&lt;a href=&quot;javascript:x&quot;&gt;b&lt;/a&gt;c</a>&lt;/a&gt;"#);
+        let mut sanitizer = Sanitizer::default();
+        assert_eq!(sanitizer.sanitize(r#"<a href="a.html">"#).as_str(), r#"<a href="a.html">"#);
+        assert_eq!(sanitizer.sanitize("</A>").as_str(), "</a>");
+        assert_eq!(sanitizer.sanitize("</a>").as_str(), "&lt;/a&gt;");
+
+        assert!(safe_url("mailto:team@example.com"));
+        assert!(safe_url("#drive(double,double)"));
+        assert!(safe_url("docs/page.html?at=12:30"));
+        assert!(!safe_url("javascript:void(0)"));
+    }
+}
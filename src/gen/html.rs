@@ -0,0 +1,424 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// The documentation as a small website, with a page for every package and type
+
+use std::io;
+
+use crate::doc::{Doc, Definition, Package};
+use crate::doc::resolve::Index;
+
+use super::escape::Html;
+use super::link::{Linker, member_id};
+use super::tags::{See, Tags, Throws, Trailing};
+use super::theme::{Style, Theme};
+use super::{search, Model, OutputBackend, Page, Sink, KINDS};
+
+/// The SS logo shown at the top of the sidebar unless the project sets its own.
+pub static DEFAULT_LOGO: &str = "https://raw.githubusercontent.com/SalineSingularityFRC/ScoutApp2020/master/app/src/main/res/drawable/robotics.png";
+
+// Format modifiers
+// for the class template in Site::class()
+macro_rules! format_modifiers {
+    ( $m:expr ) => {
+        if !$m.is_empty() {
+            format!("<h5>Modifiers</h5>\n<ul>{}</ul>", 
+                    $m.iter()
+                        .map(|m| format!("<li><code>{}</code></li>", esc!(m)))
+                        .collect::<Vec<String>>()
+                        .join("\n"))
+        } else {
+            String::new()
+        }
+    };
+}
+
+// Format the block tags of a comment to html
+// Parameters are listed in the order the method declares them, with their types
+macro_rules! format_tags {
+    ( $c:expr, $m:expr, $l:expr ) => {
+        {
+            let tags = Tags::new(&$c, $m);
+            let linker: &Linker = &$l;
+            let mut s = String::new();
+
+            if let Some(text) = tags.deprecated {
+                s += &format!("<p class=\"deprecated\"><b>Deprecated.</b> {}</p>\n", linker.text(text));
+            }
+
+            let params = tags.params.iter()
+                .map(|p| format!("<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                                 esc!(p.name),
+                                 p.ty.map(|t| format!("<code>{}</code>", esc!(t))).unwrap_or_default(),
+                                 linker.text(p.text)))
+                .collect::<Vec<String>>();
+            if !params.is_empty() {
+                s += &format!("<h5>Parameters</h5>\n<table><tr><th>Name</th><th>Type</th><th>Description</th></tr>{}</table>\n", params.join("\n"));
+            }
+
+            if let Some(r) = &tags.returns {
+                s += &format!("<h5>Returns</h5>\n<table><tr><th>Type</th><th>Description</th></tr><tr><td><code>{}</code></td><td>{}</td></tr></table>\n",
+                              esc!(r.ty.map(|t| t.to_string()).unwrap_or_default()),
+                              linker.text(r.text));
+            }
+
+            let throws = tags.throws.iter()
+                .map(|t| match t {
+                    Throws::Documented { reference, text } => format!("<tr><td>{}</td><td>{}</td></tr>", linker.link(reference, None, false), linker.text(text)),
+                    Throws::Undocumented(ty) => format!("<tr><td><code>{}</code></td><td></td></tr>", esc!(ty)),
+                })
+                .collect::<Vec<String>>();
+            if !throws.is_empty() {
+                s += &format!("<h5>Throws</h5>\n<table><tr><th>Type</th><th>Description</th></tr>{}</table>\n", throws.join("\n"));
+            }
+
+            let see = tags.see.iter()
+                .map(|t| match t {
+                    See::Text(text) => format!("<li>{}</li>", linker.text(text)),
+                    See::Reference { reference, label } => format!("<li>{}</li>", linker.link(reference, label.as_deref(), false)),
+                })
+                .collect::<Vec<String>>();
+            if !see.is_empty() {
+                s += &format!("<h5>See Also</h5>\n<ul>{}</ul>\n", see.join("\n"));
+            }
+
+            for t in &tags.trailing {
+                match t {
+                    Trailing::Since(text) => s += &format!("<h5>Since</h5>\n<p>{}</p>\n", linker.text(text)),
+                    Trailing::Author(text) => s += &format!("<h5>Author</h5>\n<p>{}</p>\n", linker.text(text)),
+                }
+            }
+            s
+        }
+    };
+}
+
+// Format fields to html
+// for the class template in Site::class()
+// Each member is a two column table, with its description across both columns and then its name
+// and definition
+macro_rules! format_fields {
+    ( $m:expr, $heading:expr, $l:expr ) => {
+        {
+            let mut s = String::new();
+            if $m.len() > 0 {
+                s = format!("<h5>{}</h5>\n", $heading);
+                for i in $m.iter() {
+                    let comment = i.comment();
+                    let linker = $l.with(i);
+                    s += format!("<table id=\"{id}\"><tr><th colspan=\"2\">Description</th></tr><tr><td colspan=\"2\">{tag}</td></tr><tr><th>Name</th><th>Definition</th></tr>{insert}</table>{tags}<br/>",
+                        insert = match &i.def {
+                            Definition::Field(f) => {
+                                format!(r"<tr><td><code>{name}</code></td><td><code>{definition}</code></td></tr>",
+                                         name = esc!(f.name),
+                                         definition = esc!(f.raw))
+                            },
+                            Definition::Method(m) => {
+                                format!(r"<tr><td><code>{name}</code></td><td><code>{definition}{default}</code></td></tr>",
+                                        name = esc!(m.name),
+                                        definition = esc!(m.raw.trim().trim_end_matches("{")),
+                                        default = match &m.default {
+                                            Some(d) => format!(" default {}", esc!(d)),
+                                            None => String::new(),
+                                        })
+                            },
+                            _ => String::new(),
+                        },
+                        id = esc!(member_id(i)),
+                        tag = linker.block(&comment.description.join("\n")),
+                        tags = format_tags!(comment, match &i.def {
+                            Definition::Method(m) => Some(m),
+                            _ => None,
+                        }, linker)
+                        ).as_str()
+                }
+            }
+            s
+        }
+    };
+}
+
+// Format the constants of an enum to html
+macro_rules! format_constants {
+    ( $c:expr, $l:expr ) => {
+        if !$c.is_empty() {
+            format!("<h5>Constants</h5>\n<table><tr><th>Name</th><th>Description</th></tr>{}</table>",
+                    $c.iter()
+                        .map(|c| format!("<tr id=\"{}\"><td><code>{}</code></td><td>{}</td></tr>",
+                                         esc!(member_id(c)),
+                                         esc!(match &c.def {
+                                             Definition::Constant(k) => k.raw.as_str(),
+                                             _ => c.name(),
+                                         }),
+                                         $l.with(c).block(&c.comment().description.join("\n"))))
+                        .collect::<Vec<String>>()
+                        .join("\n"))
+        } else {
+            String::new()
+        }
+    };
+}
+
+// Format the components of a record to html
+macro_rules! format_components {
+    ( $c:expr ) => {
+        if !$c.is_empty() {
+            format!("<h5>Components</h5>\n<table><tr><th>Name</th><th>Type</th></tr>{}</table>",
+                    $c.iter()
+                        .map(|v| format!("<tr><td><code>{}</code></td><td><code>{}</code></td></tr>", esc!(v.name), esc!(v.ty)))
+                        .collect::<Vec<String>>()
+                        .join("\n"))
+        } else {
+            String::new()
+        }
+    };
+}
+
+// Format links to the types nested in a type
+macro_rules! format_nested {
+    ( $n:expr, $root:expr ) => {
+        if !$n.is_empty() {
+            format!("<h5>Nested Types</h5>\n<ul>{}</ul>",
+                    $n.iter()
+                        .map(|n| format!("<li>{kind} <a href=\"{root}{path}\"><code>{name}</code></a></li>", kind = n.def.kind(), root = $root, path = esc!(type_path(n)), name = esc!(n.qualified_name())))
+                        .collect::<Vec<String>>()
+                        .join("\n"))
+        } else {
+            String::new()
+        }
+    };
+}
+
+// Output html for the sidebar
+// Every package is listed, along with the types in the one being looked at
+macro_rules! sidebar {
+    ( $x:expr, $root:expr, $current:expr ) => {
+        {
+            let mut s = String::new();
+            if $x.contains_classes {
+                s += &format!("<h4 class=\"sidebar-head\"><a href=\"{}index.html\">Packages</a></h4>\n", $root);
+                for p in $x.packages {
+                    s += &format!("<h5 class=\"sidebar-head\"><a href=\"{root}{path}\">{name}</a></h5>\n", root = $root, path = esc!(package_path(p)), name = esc!(p.display_name()));
+                    if $current != Some(p.name.as_str()) {
+                        continue;
+                    }
+                    s += "<ul>\n";
+                    for c in p.all_types() {
+                        s += &format!("<li class=\"sidebar-item\"><a href=\"{root}{path}\">{class}</a></li>", root = $root, path = esc!(type_path(c)), class = esc!(c.qualified_name()));
+                    }
+                    s += "</ul>\n";
+                }
+            }
+
+            // The search box, with the index it searches
+            let search = format!(concat!("<input id=\"bach-search\" class=\"search\" type=\"search\" placeholder=\"Search (/)\" autocomplete=\"off\" data-root=\"{root}\"/>\n",
+                                         "<script src=\"{root}search-index.js\"></script>\n",
+                                         "<script src=\"{root}search.js\"></script>"),
+                                 root = esc!($root));
+
+            $x.style.sidebar.render(&[
+                ("root", &esc!($root)),
+                ("logo", &esc!($x.logo)),
+                ("search", &Html::trusted(search)),
+                ("packages", &Html::trusted(s)),
+            ])
+        }
+    };
+}
+
+// Where a package's page goes, like `frc/robot/package-summary.html`
+// These paths are part of the output people link to, so don't change them lightly
+pub fn package_path(package: &Package) -> String {
+    if package.name.is_empty() {
+        String::from("package-summary.html")
+    } else {
+        format!("{}/package-summary.html", package.name.replace('.', "/"))
+    }
+}
+
+// Where a type's page goes, like `frc/robot/Outer.Inner.html`
+pub fn type_path(doc: &Doc) -> String {
+    if doc.pkg.is_empty() {
+        format!("{}.html", doc.qualified_name())
+    } else {
+        format!("{}/{}.html", doc.pkg.replace('.', "/"), doc.qualified_name())
+    }
+}
+
+// The way back to the top of the output from a page, like `../../`
+fn root_of(path: &str) -> String {
+    "../".repeat(path.matches('/').count())
+}
+
+/// Writes the documentation as html, with the stylesheet and search index its pages share.
+pub struct HtmlBackend {
+    style: Style,
+    logo: String,
+}
+
+impl HtmlBackend {
+    /// Pages styled by a loaded theme, like `Theme::Default.load()?`.
+    pub fn new(style: Style) -> Self {
+        HtmlBackend {
+            style,
+            logo: DEFAULT_LOGO.to_owned(),
+        }
+    }
+
+    /// The URL or path of the image at the top of the sidebar, instead of [`DEFAULT_LOGO`].
+    pub fn set_logo(&mut self, logo: String) {
+        self.logo = logo;
+    }
+}
+
+impl Default for HtmlBackend {
+    fn default() -> Self {
+        HtmlBackend::new(Theme::Default.load().expect("the default theme always loads"))
+    }
+}
+
+impl OutputBackend for HtmlBackend {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn generate(&self, model: &Model, sink: &mut dyn Sink) -> io::Result<()> {
+        let site = Site {
+            packages: model.packages,
+            index: &model.index,
+            style: &self.style,
+            title: &model.title,
+            logo: &self.logo,
+            contains_classes: model.contains_classes(),
+        };
+
+        sink.write("style.css", &self.style.css)?;
+        sink.write("search.js", search::SEARCH_JS)?;
+        sink.write("search-index.js", &search::script(model.packages))?;
+        let index = site.index();
+        sink.write(&index.path, &index.content)?;
+
+        for p in model.packages {
+            let page = site.package(p);
+            sink.write(&page.path, &page.content)?;
+            for c in p.all_types() {
+                let page = site.class(p, c);
+                sink.write(&page.path, &page.content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Everything the pages need while they're being made
+struct Site<'m, 'a> {
+    packages: &'a [Package<'a>],
+    // For turning `{@link}`s into links
+    index: &'m Index<'a>,
+    style: &'m Style,
+    title: &'m str,
+    logo: &'m str,
+    contains_classes: bool,
+}
+
+impl<'m, 'a> Site<'m, 'a> {
+    // Every type of one kind, like "Interface", in a package
+    fn of_kind(package: &'a Package<'a>, kind: &str) -> Vec<&'a Doc<'a>> {
+        package.all_types().into_iter().filter(|c| c.def.kind() == kind).collect()
+    }
+
+    // The front page, listing every package
+    fn index(&self) -> Page {
+        let linker = Linker::new(self.index, None, "");
+        let mut content = String::from("<h1 id=\"packages\">Packages</h1>\n");
+        content += "<table><tr><th>Package</th><th>Description</th></tr>\n";
+        for p in self.packages {
+            content += &format!("<tr><td><a href=\"{path}\"><code>{name}</code></a></td><td>{summary}</td></tr>\n",
+                                path = esc!(package_path(p)),
+                                name = esc!(p.display_name()),
+                                summary = linker.text(&p.comment().summary));
+        }
+        content += "</table>\n";
+
+        self.page(String::from("index.html"), self.title, None, content)
+    }
+
+    // A package's overview, with every type in it
+    fn package(&self, p: &'a Package<'a>) -> Page {
+        let path = package_path(p);
+        let root = root_of(&path);
+        let linker = Linker::new(self.index, None, &root);
+
+        let mut content = format!("<h1>Package <span class=\"sub\"><code>{name}</code></span></h1>\n{tag}\n",
+                                  name = esc!(p.display_name()),
+                                  tag = linker.block(&p.comment().description.join("\n")));
+
+        for (kind, heading, id) in KINDS {
+            let types = Site::of_kind(p, kind);
+            if types.is_empty() {
+                continue;
+            }
+            content += &format!("<h2 id=\"{id}\"><a href=\"#{id}\" class=\"section-head\">{heading}</a></h2>\n<table>\n", id = id, heading = heading);
+            for c in types {
+                content += &format!("<tr><td><a href=\"{root}{path}\"><code>{name}</code></a></td><td>{summary}</td></tr>\n",
+                                    root = root,
+                                    path = esc!(type_path(c)),
+                                    name = esc!(c.qualified_name()),
+                                    summary = Linker::new(self.index, Some(c), &root).text(&c.comment().summary));
+            }
+            content += "</table>\n";
+        }
+
+        self.page(path, &format!("Package {}", p.display_name()), Some(&p.name), content)
+    }
+
+    // A page for a single class, interface, enum, record or annotation type
+    fn class(&self, p: &'a Package<'a>, c: &'a Doc<'a>) -> Page {
+        let path = type_path(c);
+        let root = root_of(&path);
+        let linker = Linker::new(self.index, Some(c), &root);
+
+        let comment = c.comment();
+
+        let content = match c.def.class() {
+            Some(d) => self.style.class.render(&[
+                ("root", &esc!(root)),
+                ("package", &esc!(p.display_name())),
+                ("package_path", &esc!(package_path(p))),
+                ("kind", &esc!(c.def.kind())),
+                ("name", &esc!(c.qualified_name())),
+                ("description", &linker.block(&comment.description.join("\n"))),
+                ("tags", &Html::trusted(format_tags!(comment, None, linker))),
+                ("modifiers", &Html::trusted(format_modifiers!(d.modifiers))),
+                ("components", &Html::trusted(format_components!(d.components))),
+                ("constants", &Html::trusted(format_constants!(d.constants, linker))),
+                ("fields", &Html::trusted(format_fields!(d.fields, if let Definition::Annotation(_) = c.def { "Elements" } else { "Fields" }, linker))),
+                ("nested", &Html::trusted(format_nested!(d.nested, root))),
+                ("definition", &esc!(d.raw())),
+            ]).to_string(),
+            None => String::new(),
+        };
+
+        self.page(path, &c.full_name(), Some(&p.name), content)
+    }
+
+    // Wrap some content in the html template every page shares
+    fn page(&self, path: String, heading: &str, current: Option<&str>, content: String) -> Page {
+        let root = root_of(&path);
+
+        let content = self.style.page.render(&[
+            ("title", &esc!(self.title)),
+            ("heading", &esc!(heading)),
+            ("head", &Html::trusted(self.style.head.clone())),
+            ("root", &esc!(root)),
+            ("sidebar", &sidebar!(self, root, current)),
+            ("content", &Html::trusted(content)),
+            ("theme", &esc!(self.style.name)),
+        ]).to_string();
+
+        Page { path, content }
+    }
+}
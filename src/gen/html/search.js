@@ -0,0 +1,191 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// The search box in the sidebar
+// search-index.js has to be loaded first, it sets window.bachSearchIndex
+(function () {
+    var MAX_RESULTS = 25;
+
+    var input = document.getElementById("bach-search");
+    var index = window.bachSearchIndex || [];
+    if (!input) {
+        return;
+    }
+    var root = input.getAttribute("data-root") || "";
+
+    var list = document.createElement("ul");
+    list.className = "search-results";
+    list.hidden = true;
+    document.body.appendChild(list);
+
+    var results = [];
+    var selected = 0;
+
+    // Does this character start a word, like the `D` in `arcadeDrive` or anything after a `.`?
+    function boundary(name, i) {
+        if (i === 0) {
+            return true;
+        }
+        var prev = name.charAt(i - 1);
+        var c = name.charAt(i);
+        return prev === "." || prev === "_" || prev === "(" || (c !== c.toLowerCase() && prev === prev.toLowerCase());
+    }
+
+    // How well `query` matches `name`, or -1 if it doesn't
+    // Exact matches beat prefixes, which beat substrings, which beat letters in order
+    function score(name, query) {
+        var lower = name.toLowerCase();
+        if (lower === query) {
+            return 1000;
+        }
+        if (lower.indexOf(query) === 0) {
+            return 800 - lower.length;
+        }
+        var at = lower.indexOf(query);
+        if (at > 0) {
+            return (boundary(name, at) ? 600 : 400) - at - lower.length;
+        }
+
+        // Every letter of the query in order, with runs and the starts of words counting for more
+        var total = 0;
+        var run = 0;
+        var from = 0;
+        for (var q = 0; q < query.length; q++) {
+            var found = lower.indexOf(query.charAt(q), from);
+            if (found < 0) {
+                return -1;
+            }
+            run = found === from && q > 0 ? run + 1 : 0;
+            total += 1 + run * 2 + (boundary(name, found) ? 3 : 0);
+            from = found + 1;
+        }
+        return 100 + total * 4 - lower.length;
+    }
+
+    // Types come before the members of them when the match is just as good
+    var KIND_BONUS = {
+        "Package": 3,
+        "Class": 5,
+        "Interface": 5,
+        "Enum": 5,
+        "Record": 5,
+        "Annotation Type": 5
+    };
+
+    function search(query) {
+        query = query.trim().toLowerCase();
+        if (!query) {
+            return [];
+        }
+
+        var found = [];
+        for (var i = 0; i < index.length; i++) {
+            var e = index[i];
+            var s = score(e.n, query);
+            // `SingDrive.arcade` finds members through the type they're in
+            var full = e.q ? score(e.q + "." + e.n, query) - 50 : -1;
+            s = Math.max(s, full);
+            if (s >= 0) {
+                found.push({ entry: e, score: s + (KIND_BONUS[e.k] || 0) });
+            }
+        }
+
+        found.sort(function (a, b) {
+            return b.score - a.score || a.entry.n.localeCompare(b.entry.n);
+        });
+        return found.slice(0, MAX_RESULTS).map(function (f) { return f.entry; });
+    }
+
+    function show() {
+        list.innerHTML = "";
+        list.hidden = results.length === 0;
+        results.forEach(function (e, i) {
+            var item = document.createElement("li");
+            if (i === selected) {
+                item.className = "selected";
+            }
+
+            var link = document.createElement("a");
+            link.href = root + e.p;
+
+            // textContent keeps anything in the names from being read as html
+            var name = document.createElement("code");
+            name.textContent = e.n;
+            var kind = document.createElement("span");
+            kind.className = "search-kind";
+            kind.textContent = " " + e.k + (e.q ? " in " + e.q : "");
+            link.appendChild(name);
+            link.appendChild(kind);
+            if (e.s) {
+                var summary = document.createElement("div");
+                summary.className = "search-summary";
+                summary.textContent = e.s;
+                link.appendChild(summary);
+            }
+
+            item.appendChild(link);
+            item.addEventListener("mousemove", function () {
+                if (selected !== i) {
+                    selected = i;
+                    show();
+                }
+            });
+            list.appendChild(item);
+        });
+
+        var current = list.children[selected];
+        if (current && current.scrollIntoView) {
+            current.scrollIntoView({ block: "nearest" });
+        }
+    }
+
+    input.addEventListener("input", function () {
+        results = search(input.value);
+        selected = 0;
+        show();
+    });
+
+    input.addEventListener("keydown", function (ev) {
+        if (ev.key === "ArrowDown") {
+            selected = Math.min(selected + 1, results.length - 1);
+            show();
+            ev.preventDefault();
+        } else if (ev.key === "ArrowUp") {
+            selected = Math.max(selected - 1, 0);
+            show();
+            ev.preventDefault();
+        } else if (ev.key === "Enter") {
+            if (results[selected]) {
+                window.location.href = root + results[selected].p;
+            }
+            ev.preventDefault();
+        } else if (ev.key === "Escape") {
+            input.value = "";
+            results = [];
+            show();
+            input.blur();
+        }
+    });
+
+    // `/` jumps to the search box from anywhere on the page
+    document.addEventListener("keydown", function (ev) {
+        var typing = document.activeElement && /^(INPUT|TEXTAREA|SELECT)$/.test(document.activeElement.tagName);
+        if (ev.key === "/" && !typing) {
+            input.focus();
+            ev.preventDefault();
+        }
+    });
+
+    // Clicking anywhere else closes the results
+    document.addEventListener("click", function (ev) {
+        if (ev.target !== input && !list.contains(ev.target)) {
+            list.hidden = true;
+        }
+    });
+    input.addEventListener("focus", function () {
+        list.hidden = results.length === 0;
+    });
+})();
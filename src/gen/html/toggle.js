@@ -0,0 +1,49 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// Switches the auto theme between light and dark, remembering the choice
+// This runs in the <head> so a saved choice is applied before anything is drawn
+(function () {
+    var key = "bach-theme";
+    var root = document.documentElement;
+
+    // localStorage can be turned off, in which case the toggle just doesn't stick
+    try {
+        var saved = localStorage.getItem(key);
+        if (saved === "light" || saved === "dark") {
+            root.setAttribute("data-theme", saved);
+        }
+    } catch (e) {}
+
+    function current() {
+        var chosen = root.getAttribute("data-theme");
+        if (chosen) {
+            return chosen;
+        }
+        return window.matchMedia && window.matchMedia("(prefers-color-scheme: dark)").matches ? "dark" : "light";
+    }
+
+    document.addEventListener("DOMContentLoaded", function () {
+        var button = document.createElement("button");
+        button.className = "theme-toggle";
+
+        function label() {
+            button.textContent = current() === "dark" ? "Light mode" : "Dark mode";
+        }
+
+        button.addEventListener("click", function () {
+            var next = current() === "dark" ? "light" : "dark";
+            root.setAttribute("data-theme", next);
+            try {
+                localStorage.setItem(key, next);
+            } catch (e) {}
+            label();
+        });
+
+        label();
+        document.body.appendChild(button);
+    });
+})();
@@ -0,0 +1,441 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// The documentation as one JSON file, for other tools to read
+// These structs are the schema, described in the README, and are kept apart from the model so
+// the model can change without breaking anyone. Adding a key is fine, but renaming or removing
+// one, or changing what it means, needs SCHEMA_VERSION to go up
+
+use std::io;
+
+use serde::Serialize;
+
+use crate::doc::comment;
+use crate::doc::resolve::Index;
+use crate::doc::{self, BlockTag, Bound, ConstantDef, Definition, Doc, Variable};
+
+use super::link::member_id;
+use super::{Model, OutputBackend, Sink};
+
+// Bumped whenever the schema changes in a way that could break a reader
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Writes everything into `bach.json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonBackend;
+
+impl OutputBackend for JsonBackend {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn generate(&self, model: &Model, sink: &mut dyn Sink) -> io::Result<()> {
+        sink.write("bach.json", &write(&model.title, model.packages, &model.index))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Document<'d> {
+    pub schema_version: u32,
+    // The version of bach that wrote it
+    pub generator: String,
+    pub title: &'d str,
+    pub packages: Vec<Package<'d>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Package<'d> {
+    // Empty for the default package
+    pub name: &'d str,
+    pub comment: Option<Comment>,
+    pub types: Vec<Type<'d>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Type<'d> {
+    // "class", "interface", "enum", "record" or "annotation"
+    pub kind: &'static str,
+    pub name: &'d str,
+    // With the types it's nested in, like `Outer.Inner`
+    pub qualified_name: String,
+    // With the package too, like `frc.robot.Outer.Inner`
+    pub full_name: String,
+    pub location: Location,
+    pub modifiers: &'d [String],
+    pub annotations: &'d [String],
+    pub type_params: Vec<TypeRef<'d>>,
+    pub extends: Vec<TypeRef<'d>>,
+    pub implements: Vec<TypeRef<'d>>,
+    pub components: Vec<Param<'d>>,
+    // The declaration as written
+    pub definition: &'d str,
+    pub comment: Option<Comment>,
+    pub constants: Vec<Constant<'d>>,
+    pub members: Vec<Member<'d>>,
+    pub nested: Vec<Type<'d>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Constant<'d> {
+    pub name: &'d str,
+    pub location: Location,
+    pub annotations: &'d [String],
+    // The arguments to the enum's constructor, as written
+    pub args: Option<&'d str>,
+    pub definition: &'d str,
+    pub comment: Option<Comment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Member<'d> {
+    // "field", "method", "constructor" or "element" (of an annotation type)
+    pub kind: &'static str,
+    pub name: &'d str,
+    // Tells overloads apart, like `drive(double,double)`, and is what links use after the `#`
+    pub id: String,
+    pub location: Location,
+    pub modifiers: &'d [String],
+    pub annotations: &'d [String],
+    pub type_params: Vec<TypeRef<'d>>,
+    // The field's type or what the method returns, null for constructors
+    #[serde(rename = "type")]
+    pub ty: Option<TypeRef<'d>>,
+    pub params: Vec<Param<'d>>,
+    pub throws: Vec<TypeRef<'d>>,
+    // A field's initializer
+    pub value: Option<&'d str>,
+    // An annotation element's default
+    pub default: Option<&'d str>,
+    pub definition: &'d str,
+    pub comment: Option<Comment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Location {
+    pub file: String,
+    // Where the declaration starts, after its comment, 1-based
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Param<'d> {
+    pub name: &'d str,
+    #[serde(rename = "type")]
+    pub ty: TypeRef<'d>,
+    pub varargs: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypeRef<'d> {
+    // The name as written, or `?` for a wildcard
+    pub name: &'d str,
+    pub args: Vec<TypeRef<'d>>,
+    pub dims: usize,
+    // "extends" or "super"
+    pub bound_kind: Option<&'static str>,
+    pub bounds: Vec<TypeRef<'d>>,
+    // The whole thing as Java, like `List<? extends Motor>[]`
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Comment {
+    // The line the first of `raw` is on, or None for package overviews, which have no file
+    pub line: Option<usize>,
+    // The comment's lines as written, without the `///` or `*`
+    pub raw: Vec<String>,
+    pub summary: String,
+    // Markdown, with any inline tags left in
+    pub description: String,
+    pub tags: Vec<Tag>,
+    pub links: Vec<Link>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "tag", rename_all = "lowercase")]
+pub enum Tag {
+    Param { name: String, text: String },
+    Return { text: String },
+    Throws {
+        #[serde(rename = "type")]
+        ty: String,
+        text: String,
+    },
+    See { text: String },
+    Since { text: String },
+    Deprecated { text: String },
+    Author { text: String },
+    Other { name: String, text: String },
+}
+
+// A reference in a comment, from a `{@link}`, `{@value}` or `@see`
+#[derive(Debug, Serialize)]
+pub struct Link {
+    pub reference: String,
+    // In the file, or counted from the start of the comment for package overviews
+    pub line: usize,
+    // To something outside the project, like `java.*`, `String` or an imported library's type,
+    // which is never resolved
+    pub external: bool,
+    pub target: Option<LinkTarget>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkTarget {
+    // The full name of the type
+    #[serde(rename = "type")]
+    pub ty: String,
+    // The member's id, if the link is to one
+    pub member: Option<String>,
+}
+
+// Turn the documentation into the JSON schema, pretty printed
+pub fn write(title: &str, packages: &[doc::Package], index: &Index) -> String {
+    let document = Document {
+        schema_version: SCHEMA_VERSION,
+        generator: format!("bach {}", env!("CARGO_PKG_VERSION")),
+        title,
+        packages: packages.iter().map(|p| Package {
+            name: &p.name,
+            comment: comment(&p.tag, None, None, index),
+            types: p.types.iter().map(|t| ty(t, index)).collect(),
+        }).collect(),
+    };
+    serde_json::to_string_pretty(&document).expect("the documentation is always valid JSON") + "\n"
+}
+
+fn ty<'d>(t: &'d Doc<'d>, index: &Index<'d>) -> Type<'d> {
+    let c = t.def.class().expect("only types are listed as types");
+    let annotation = matches!(t.def, Definition::Annotation(_));
+    Type {
+        kind: match t.def {
+            Definition::Interface(_) => "interface",
+            Definition::Enum(_) => "enum",
+            Definition::Record(_) => "record",
+            Definition::Annotation(_) => "annotation",
+            _ => "class",
+        },
+        name: t.name(),
+        qualified_name: t.qualified_name(),
+        full_name: t.full_name(),
+        location: location(t),
+        modifiers: &c.modifiers,
+        annotations: &c.annotations,
+        type_params: refs(&c.type_params),
+        extends: refs(&c.extends),
+        implements: refs(&c.implements),
+        components: c.components.iter().map(param).collect(),
+        definition: c.raw(),
+        comment: comment(&t.tag, Some(t.line), Some(t), index),
+        constants: c.constants.iter().filter_map(|k| match &k.def {
+            Definition::Constant(d) => Some(constant(k, d, t, index)),
+            _ => None,
+        }).collect(),
+        members: c.fields.iter().filter_map(|m| member(m, annotation, t, index)).collect(),
+        nested: c.nested.iter().map(|n| ty(n, index)).collect(),
+    }
+}
+
+fn constant<'d>(k: &'d Doc<'d>, d: &'d ConstantDef, parent: &'d Doc<'d>, index: &Index<'d>) -> Constant<'d> {
+    Constant {
+        name: &d.name,
+        location: location(k),
+        annotations: &d.annotations,
+        args: d.args.as_deref(),
+        definition: &d.raw,
+        comment: comment(&k.tag, Some(k.line), Some(parent), index),
+    }
+}
+
+fn member<'d>(m: &'d Doc<'d>, annotation: bool, parent: &'d Doc<'d>, index: &Index<'d>) -> Option<Member<'d>> {
+    let member = match &m.def {
+        Definition::Field(f) => Member {
+            kind: "field",
+            name: &f.name,
+            id: member_id(m),
+            location: location(m),
+            modifiers: &f.modifiers,
+            annotations: &f.annotations,
+            type_params: Vec::new(),
+            ty: Some(type_ref(&f.ty)),
+            params: Vec::new(),
+            throws: Vec::new(),
+            value: f.value.as_deref(),
+            default: None,
+            definition: &f.raw,
+            comment: comment(&m.tag, Some(m.line), Some(parent), index),
+        },
+        Definition::Method(d) => Member {
+            kind: if d.is_constructor() {
+                "constructor"
+            } else if annotation {
+                "element"
+            } else {
+                "method"
+            },
+            name: &d.name,
+            id: member_id(m),
+            location: location(m),
+            modifiers: &d.modifiers,
+            annotations: &d.annotations,
+            type_params: refs(&d.type_params),
+            ty: d.ret.as_ref().map(type_ref),
+            params: d.args.iter().map(param).collect(),
+            throws: refs(&d.throws),
+            value: None,
+            default: d.default.as_deref(),
+            definition: d.raw.trim().trim_end_matches('{').trim_end(),
+            comment: comment(&m.tag, Some(m.line), Some(parent), index),
+        },
+        _ => return None,
+    };
+    Some(member)
+}
+
+fn location(d: &Doc) -> Location {
+    Location {
+        file: d.file.display().to_string(),
+        line: d.pos.0,
+        column: d.pos.1,
+    }
+}
+
+fn param(v: &Variable) -> Param<'_> {
+    Param {
+        name: &v.name,
+        ty: type_ref(&v.ty),
+        varargs: v.varargs,
+    }
+}
+
+fn refs(types: &[doc::TypeRef]) -> Vec<TypeRef<'_>> {
+    types.iter().map(type_ref).collect()
+}
+
+fn type_ref(t: &doc::TypeRef) -> TypeRef<'_> {
+    let (bound_kind, bounds) = match &t.bound {
+        Some(Bound::Extends(b)) => (Some("extends"), refs(b)),
+        Some(Bound::Super(b)) => (Some("super"), vec![type_ref(b)]),
+        None => (None, Vec::new()),
+    };
+    TypeRef {
+        name: &t.name,
+        args: refs(&t.args),
+        dims: t.dims,
+        bound_kind,
+        bounds,
+        text: t.to_string(),
+    }
+}
+
+// A comment with its tags parsed and its links looked up from `context`
+// Nothing at all comes out as null, rather than an empty comment
+// `line` is where its first line is, and without one the lines of links count from 0
+fn comment<'d>(lines: &[String], line: Option<usize>, context: Option<&'d Doc<'d>>, index: &Index<'d>) -> Option<Comment> {
+    if lines.is_empty() {
+        return None;
+    }
+    let parsed = comment::Comment::parse(lines);
+
+    let links = comment::references(lines).into_iter().map(|(n, reference)| {
+        let external = index.is_external(&reference, context);
+        let target = if external {
+            None
+        } else {
+            index.resolve(&reference, context).map(|t| LinkTarget {
+                ty: t.ty.full_name(),
+                member: t.member.map(member_id),
+            })
+        };
+        Link {
+            reference,
+            line: line.unwrap_or(0) + n,
+            external,
+            target,
+        }
+    }).collect();
+
+    Some(Comment {
+        line,
+        raw: lines.to_vec(),
+        summary: parsed.summary,
+        description: parsed.description.join("\n"),
+        tags: parsed.tags.into_iter().map(|t| match t {
+            BlockTag::Param { name, text } => Tag::Param { name, text },
+            BlockTag::Return(text) => Tag::Return { text },
+            BlockTag::Throws { ty, text } => Tag::Throws { ty, text },
+            BlockTag::See(text) => Tag::See { text },
+            BlockTag::Since(text) => Tag::Since { text },
+            BlockTag::Deprecated(text) => Tag::Deprecated { text },
+            BlockTag::Author(text) => Tag::Author { text },
+            BlockTag::Other { name, text } => Tag::Other { name, text },
+        }).collect(),
+        links,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::scan::scan_source;
+
+    fn keys(v: &Value) -> Vec<&str> {
+        let mut keys = v.as_object().unwrap().keys().map(|k| k.as_str()).collect::<Vec<&str>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn schema() {
+        let src = "package frc.robot;\n\
+                   /** Drives, see {@link String}. */\n\
+                   public class Drive {\n\
+                   \x20   /**\n\
+                   \x20    * Drives.\n\
+                   \x20    * @param speed how fast\n\
+                   \x20    */\n\
+                   \x20   public void drive(double speed) {}\n\
+                   }\n";
+        let packages = doc::Package::group(scan_source(Path::new("Drive.java"), src).docs, Vec::new());
+        let json: Value = serde_json::from_str(&write("Robot", &packages, &Index::new(&packages))).unwrap();
+
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(keys(&json), vec!["generator", "packages", "schema_version", "title"]);
+
+        let package = &json["packages"][0];
+        assert_eq!(keys(package), vec!["comment", "name", "types"]);
+        assert_eq!(package["name"], "frc.robot");
+
+        let ty = &package["types"][0];
+        assert_eq!(keys(ty), vec![
+            "annotations", "comment", "components", "constants", "definition", "extends", "full_name", "implements",
+            "kind", "location", "members", "modifiers", "name", "nested", "qualified_name", "type_params",
+        ]);
+        assert_eq!(ty["kind"], "class");
+        assert_eq!(ty["full_name"], "frc.robot.Drive");
+        assert_eq!(keys(&ty["location"]), vec!["column", "file", "line"]);
+
+        let comment = &ty["comment"];
+        assert_eq!(keys(comment), vec!["description", "line", "links", "raw", "summary", "tags"]);
+        assert_eq!(keys(&comment["links"][0]), vec!["external", "line", "reference", "target"]);
+        assert_eq!(comment["links"][0]["external"], true);
+
+        let member = &ty["members"][0];
+        assert_eq!(keys(member), vec![
+            "annotations", "comment", "default", "definition", "id", "kind", "location", "modifiers", "name",
+            "params", "throws", "type", "type_params", "value",
+        ]);
+        assert_eq!(member["id"], "drive(double)");
+        assert_eq!(keys(&member["params"][0]), vec!["name", "type", "varargs"]);
+        assert_eq!(keys(&member["type"]), vec!["args", "bound_kind", "bounds", "dims", "name", "text"]);
+        assert_eq!(member["comment"]["tags"][0], serde_json::json!({ "tag": "param", "name": "speed", "text": "how fast" }));
+    }
+}
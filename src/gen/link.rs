@@ -0,0 +1,158 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use crate::doc::comment::Inline;
+use crate::doc::resolve::{Index, Target};
+use crate::doc::{Definition, Doc};
+
+use super::escape::Html;
+use super::commonmark;
+use super::type_path;
+
+// The anchor of a member on its type's page
+// Methods include their parameter types, like `drive(double,double)`, so overloads don't collide
+pub fn member_id(doc: &Doc) -> String {
+    match &doc.def {
+        Definition::Method(m) => format!("{}({})",
+                                         m.name,
+                                         m.args
+                                            .iter()
+                                            .map(|a| format!("{}{}{}", a.ty.simple_name(), "[]".repeat(a.ty.dims), if a.varargs { "..." } else { "" }))
+                                            .collect::<Vec<String>>()
+                                            .join(",")),
+        _ => doc.name().to_owned(),
+    }
+}
+
+// Finds what the references in some doc text point at, for every backend
+#[derive(Clone, Copy)]
+pub struct Resolver<'g, 'a> {
+    index: &'g Index<'a>,
+    // The type the text is in, for references like `#member`
+    context: Option<&'a Doc<'a>>,
+    // What's being documented, for a `{@value}` on its own
+    this: Option<&'a Doc<'a>>,
+}
+
+impl<'g, 'a> Resolver<'g, 'a> {
+    pub fn new(index: &'g Index<'a>, context: Option<&'a Doc<'a>>) -> Self {
+        Resolver {
+            index,
+            context,
+            this: context,
+        }
+    }
+
+    // The same resolver, for text on a member of the type
+    pub fn with(&self, this: &'a Doc<'a>) -> Self {
+        Resolver {
+            this: Some(this),
+            ..*self
+        }
+    }
+
+    // Where a reference goes, if it's to something in the documentation
+    pub fn link(&self, reference: &str) -> Option<Target<'a>> {
+        if self.index.is_external(reference, self.context) {
+            return None;
+        }
+        self.index.resolve(reference, self.context)
+    }
+
+    // The field a `{@value}` is for and its value, as far as they're known
+    pub fn value(&self, reference: Option<&str>) -> (Option<Target<'a>>, Option<String>) {
+        let target = match reference {
+            Some(r) => self.index.resolve(r, self.context),
+            None => self.this.and_then(|d| self.context.map(|c| Target { ty: c, member: Some(d) })),
+        };
+
+        let value = target.and_then(|t| match t.member.map(|m| &m.def) {
+            Some(Definition::Field(f)) => f.value.clone(),
+            _ => None,
+        });
+        (target, value)
+    }
+}
+
+// Turns doc text into html, with links relative to the page it's going on
+#[derive(Clone, Copy)]
+pub struct Linker<'g, 'a> {
+    resolver: Resolver<'g, 'a>,
+    root: &'g str,
+}
+
+impl<'g, 'a> Linker<'g, 'a> {
+    pub fn new(index: &'g Index<'a>, context: Option<&'a Doc<'a>>, root: &'g str) -> Self {
+        Linker {
+            resolver: Resolver::new(index, context),
+            root,
+        }
+    }
+
+    // The same linker, for text on a member of the type
+    pub fn with(&self, this: &'a Doc<'a>) -> Self {
+        Linker {
+            resolver: self.resolver.with(this),
+            ..*self
+        }
+    }
+
+    // Where a resolved reference goes, relative to the page
+    pub fn href(&self, target: &Target) -> String {
+        match target.member {
+            Some(m) => format!("{}{}#{}", self.root, type_path(target.ty), member_id(m)),
+            None => format!("{}{}", self.root, type_path(target.ty)),
+        }
+    }
+
+    // Render a whole description, Markdown and all
+    pub fn block(&self, text: &str) -> Html {
+        commonmark::render(text, |i| self.tag(i))
+    }
+
+    // Render a short bit of doc text, like a summary or the text of a block tag
+    pub fn text(&self, text: &str) -> Html {
+        commonmark::render_inline(text, |i| self.tag(i))
+    }
+
+    // Render one inline tag, turning references that resolve into links
+    fn tag(&self, i: &Inline) -> Html {
+        match i {
+            Inline::Text(t) | Inline::Literal(t) => esc!(t),
+            Inline::Code(c) => Html::trusted(format!("<code>{}</code>", esc!(c))),
+            Inline::Link { target, label, plain } => self.link(target, label.as_deref(), *plain),
+            Inline::Value(target) => self.value(target.as_deref()),
+            Inline::Other { text, .. } => esc!(text),
+        }
+    }
+
+    // A link to a reference, or just its name if it doesn't go anywhere
+    pub fn link(&self, reference: &str, label: Option<&str>, plain: bool) -> Html {
+        let label = match label {
+            Some(l) => self.text(l),
+            None if plain => esc!(display(reference)),
+            None => Html::trusted(format!("<code>{}</code>", esc!(display(reference)))),
+        };
+
+        match self.resolver.link(reference) {
+            Some(t) => Html::trusted(format!("<a href=\"{}\">{}</a>", esc!(self.href(&t)), label)),
+            None => label,
+        }
+    }
+
+    // The value of a field, linked to it
+    fn value(&self, reference: Option<&str>) -> Html {
+        match self.resolver.value(reference) {
+            (Some(t), Some(v)) => Html::trusted(format!("<a href=\"{}\"><code>{}</code></a>", esc!(self.href(&t)), esc!(v))),
+            _ => Html::trusted(format!("<code>{}</code>", esc!(reference.map(display).unwrap_or_default()))),
+        }
+    }
+}
+
+// How a reference is shown when it has no label, like `Foo.bar(int)` for `Foo#bar(int)`
+pub fn display(reference: &str) -> String {
+    reference.trim_start_matches('#').replace('#', ".")
+}
@@ -4,199 +4,94 @@
 //  file, You can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
+#[macro_use]
+mod escape;
+mod backend;
+mod commonmark;
+pub mod html;
+pub mod json;
+mod link;
+mod search;
+mod tags;
+mod template;
 mod theme;
-pub use theme::Theme;
-
-
-use crate::doc::{Doc, Definition};
-
-// Format modifiers
-// for the class template in Generator::generate()
-macro_rules! format_modifiers {
-    ( $m:expr ) => {
-        if $m.len() != 0 {
-            format!("<h5>Modifiers</h5>\n<ul>{}</ul>", 
-                    $m.trim()
-                        .split(" ")
-                        .map(|m| format!("<li><code>{}</code></li>", m))
-                        .collect::<Vec<String>>()
-                        .join("\n"))
-        } else {
-            String::new()
-        }
-    };
+pub mod wiki;
+pub use backend::{DirSink, OutputBackend, Sink};
+pub use escape::{escape, Html};
+pub use html::{package_path, type_path, HtmlBackend, DEFAULT_LOGO};
+pub use json::JsonBackend;
+pub use template::{Template, TemplateError};
+pub use theme::{Style, Theme, ThemeError};
+pub use wiki::MarkdownBackend;
+
+use serde::Deserialize;
+
+use crate::doc::Package;
+use crate::doc::resolve::Index;
+
+// Every kind of type, with the heading and html anchor of its section on a package's page
+static KINDS: &[(&str, &str, &str)] = &[
+    ("Class", "Classes", "classes"),
+    ("Interface", "Interfaces", "interfaces"),
+    ("Enum", "Enums", "enums"),
+    ("Record", "Records", "records"),
+    ("Annotation Type", "Annotation Types", "annotations"),
+];
+
+/// The kinds of output bach has a backend for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// A website, with a page for each package and type.
+    Html,
+    /// Everything bach knows, in one file for other tools to read.
+    Json,
+    /// GitHub-flavored Markdown for a wiki.
+    Markdown,
 }
 
-// Format fields to html
-// for the class template in Generator::generate()
-// TODO(@monarrk): what the fuck
-macro_rules! format_fields {
-    ( $m:expr ) => {
-        {
-            let mut s = String::new();
-            if $m.len() > 0 {
-                s = String::from("<h5>Fields</h5>\n");
-                for i in $m.iter() {
-                    s += format!("<table><tr><th>Description</th</tr><td><code>{tag}</code></td><tr><th>Name</th><th>Definition</th></tr>{insert}</table><br/>",
-                        insert = match &i.def {
-                            Definition::Field(f) => {
-                                format!(r"<tr><td><code>{name}</code></td><td><code>{definition}</code></td></tr>",
-                                         name = f.name,
-                                         definition = f.raw)
-                            },
-                            Definition::Method(m) => {
-                                format!(r"<tr><td><code>{name}</code></td><td><code>{definition}</code></td></tr>",
-                                        name = m.name,
-                                        definition = m.raw.trim().trim_end_matches("{"))
-                            },
-                            _ => String::new(),
-                        }, 
-                        tag = i.tag.iter()
-                            .map(|t| t.trim().trim_start_matches("///"))
-                            .collect::<Vec<&str>>()
-                            .join("<br/>")
-                        ).as_str()
-                }
-            }
-            s
+impl Format {
+    /// A format from its name, in any case, like `"html"`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "html" => Some(Format::Html),
+            "json" => Some(Format::Json),
+            "markdown" => Some(Format::Markdown),
+            _ => None,
         }
-    };
+    }
 }
 
-// Output html for the sidebar
-macro_rules! sidebar {
-    ( $x:expr ) => {
-        {
-            let mut s = String::new();
-            
-            // Add the SS logo
-            s += r#"<img src="https://raw.githubusercontent.com/SalineSingularityFRC/ScoutApp2020/master/app/src/main/res/drawable/robotics.png" width="150" height="150"/>"#;
-            if $x.contains_classes() {
-                s += "<h4 class=\"sidebar-head\"><a href=\"#classes\">Classes</a></h4>\n";
-                s += "<ul>\n";
-                for c in &$x.classes {
-                    s += &format!("<li class=\"sidebar-item\"><a href=\"#class-{class}\">{class}</a></li>", class = c.name());
-                }
-                s += "</ul>\n";
-            }
-            s
-        }
-    };
+/// A single file of output, kept in memory by `Vec<Page>` as a [`Sink`].
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// Relative to the top of the output, like `frc/robot/Robot.html`.
+    pub path: String,
+    pub content: String,
 }
 
-// A generator type for generating the documentation
-pub struct Generator<'a> {
-    pub(crate) classes: Vec<&'a Doc<'a>>,
-    theme: Theme,
-    title: String,
-    css: String,
-    content: String,
-    header: String,
+/// Everything an [`OutputBackend`] is given: the documentation, and an index of it for resolving
+/// references.
+pub struct Model<'a> {
+    pub title: String,
+    /// Every package, ordered by name, with its top level types.
+    pub packages: &'a [Package<'a>],
+    /// Looks up the references in comments, like `{@link Robot#drive}`.
+    pub index: Index<'a>,
 }
 
-impl<'a> Generator<'a> {
-    pub fn new(title: String, classes: Vec<&'a Doc>, theme: Theme) -> Self {
-        Generator {
-            classes,
-            theme,
+impl<'a> Model<'a> {
+    /// Index some packages for a backend.
+    pub fn new(title: String, packages: &'a [Package<'a>]) -> Self {
+        Model {
             title,
-            ..Generator::default()
+            packages,
+            index: Index::new(packages),
         }
     }
 
-    // allow dead code for now because we don't use this yet
-    #[allow(dead_code)]
-    pub fn set_theme(&mut self, theme: Theme) {
-        self.theme = theme;
-    }
-
+    /// Is there at least one type anywhere, rather than just package overviews?
     pub fn contains_classes(&self) -> bool {
-        self.classes.len() != 0
-    }
-
-    // Return a String of generated HTML derived from the information
-    pub fn generate(&mut self) -> String {
-        // Set the easy stuff
-        self.css = self.theme.get();
-        self.header = format!(r#"<h1>Package {}</h1>"#, self.title);
-
-        self.content += "<h1 id=\"classes\"><a href=\"#classes\" class=\"section-head\">Classes</a></h1>\n";
-        for c in self.classes.iter() {
-            // Unwrap our class
-            // TODO(@monarrk): Make this safe? Probably?
-            let d = match &c.def {
-                Definition::Class(c) => c,
-                _ => panic!("Not a class!"),
-            };
-
-            let tag = c.tag
-                .iter()
-                .map(|s| s.trim().trim_start_matches("///"))
-                .collect::<Vec<&str>>()
-                .join("<br>");
-
-            // Add a new block to the content with our class
-            self.content += &format!(r#"
-                                    <hr/>
-                                    <div class="block">
-                                    <h3>Class <span class="sub" id="class-{title}"><b><code>{title}</code></b></span></h3>
-                                    <p>{tag}<p>
-                                    {modifiers}
-                                    {fields}
-                                    <h5>Definition</h5>
-                                    <p><code>{definition}</code></p>
-                                    </div>
-                                     "#, 
-                                     title = d.get_name(),
-                                     tag = tag,
-                                     definition = d.raw(),
-                                     modifiers = format_modifiers!(d.modifiers),
-                                     fields = format_fields!(d.fields));
-        }
-
-        // html template
-        format!(r#"
-                <!DOCTYPE html>
-                <html>
-                    <head>
-                        <meta charset="utf-8"/>
-                        <title>Package {title}</title>
-                        <style>
-                            {css}
-                        </style>
-                    </head>
-                    <body>
-                        <div class="sidebar">
-                            {bar}
-                        </div>
-
-                        <div class="main">
-                            {head}
-                            {content}
-
-                            <br/>
-                            <h6>Generated with <a href="https://github.com/SalineSingularityFRC/bach" target="_blank">Bach</a> with the {theme} theme</h6>
-                        </div>
-                    </body>
-                </html>"#,
-                title = self.title,
-                css = self.css,
-                head = self.header,
-                content = self.content,
-                bar = sidebar!(self),
-                theme = self.theme.name())
-    }
-}
-
-impl Default for Generator<'_> {
-    fn default() -> Self {
-        Generator {
-            classes: Vec::new(),
-            theme: Theme::Default,
-            title: String::new(),
-            css: String::new(),
-            content: String::new(),
-            header: String::new(),
-        }
+        self.packages.iter().any(|p| !p.types.is_empty())
     }
 }
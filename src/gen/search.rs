@@ -0,0 +1,81 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use serde::Serialize;
+
+use crate::doc::comment;
+use crate::doc::{Definition, Package};
+
+use super::link::member_id;
+use super::{package_path, type_path};
+
+// The search box, which search.js brings to life
+pub static SEARCH_JS: &str = include_str!("html/search.js");
+
+// One thing that can be searched for
+// The names are kept short because there's one of these for everything in the project
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    // The name it's found by, like `arcadeDrive`
+    pub n: String,
+    // What it's in, like `frc.robot.SingDrive`, shown next to the name
+    pub q: String,
+    // The kind, like "Method"
+    pub k: &'static str,
+    // The page it's on, relative to the top of the output
+    pub p: String,
+    // The first sentence of its comment
+    pub s: String,
+}
+
+// Everything in the project that can be searched for, packages first
+pub fn entries(packages: &[Package]) -> Vec<Entry> {
+    let mut out = Vec::new();
+    for p in packages {
+        out.push(Entry {
+            n: p.display_name().to_owned(),
+            q: String::new(),
+            k: "Package",
+            p: package_path(p),
+            s: comment::plain_text(&p.comment().summary),
+        });
+
+        for t in p.all_types() {
+            out.push(Entry {
+                n: t.qualified_name(),
+                q: p.name.clone(),
+                k: t.def.kind(),
+                p: type_path(t),
+                s: comment::plain_text(&t.comment().summary),
+            });
+
+            let class = match t.def.class() {
+                Some(c) => c,
+                None => continue,
+            };
+            for m in class.constants.iter().chain(class.fields.iter()) {
+                let name = match &m.def {
+                    Definition::Method(_) => member_id(m).replace(',', ", "),
+                    _ => m.name().to_owned(),
+                };
+                out.push(Entry {
+                    n: name,
+                    q: t.full_name(),
+                    k: m.def.kind(),
+                    p: format!("{}#{}", type_path(t), member_id(m)),
+                    s: comment::plain_text(&m.comment().summary),
+                });
+            }
+        }
+    }
+    out
+}
+
+// The index as a script, so it loads from file:// where fetching a .json file isn't allowed
+pub fn script(packages: &[Package]) -> String {
+    let json = serde_json::to_string(&entries(packages)).unwrap_or_else(|_| String::from("[]"));
+    format!("window.bachSearchIndex = {};\n", json)
+}
@@ -0,0 +1,117 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// The block tags of a comment, sorted into the sections every backend shows them in
+// The backends only decide how each section looks
+
+use crate::doc::comment::split_reference;
+use crate::doc::{BlockTag, Comment, MethodDef, TypeRef};
+
+pub struct Tags<'c> {
+    pub deprecated: Option<&'c str>,
+    // The method's parameters in the order it declares them, then any others, like type parameters
+    pub params: Vec<Param<'c>>,
+    // Unless it's a constructor or void with nothing to say
+    pub returns: Option<Returns<'c>>,
+    // Exceptions that are documented, then any others in the `throws` clause
+    pub throws: Vec<Throws<'c>>,
+    pub see: Vec<See<'c>>,
+    // @since and @author, in the order they're written
+    pub trailing: Vec<Trailing<'c>>,
+}
+
+pub struct Param<'c> {
+    pub name: &'c str,
+    pub ty: Option<&'c TypeRef>,
+    pub text: &'c str,
+}
+
+pub struct Returns<'c> {
+    pub ty: Option<&'c TypeRef>,
+    pub text: &'c str,
+}
+
+pub enum Throws<'c> {
+    Documented { reference: &'c str, text: &'c str },
+    Undocumented(&'c TypeRef),
+}
+
+pub enum See<'c> {
+    // `@see "Some text"` and `@see <a href=...>` are kept as they are
+    Text(&'c str),
+    Reference { reference: String, label: Option<String> },
+}
+
+pub enum Trailing<'c> {
+    Since(&'c str),
+    Author(&'c str),
+}
+
+impl<'c> Tags<'c> {
+    pub fn new(comment: &'c Comment, method: Option<&'c MethodDef>) -> Self {
+        let mut params = Vec::new();
+        if let Some(m) = method {
+            for a in &m.args {
+                params.push(Param {
+                    name: &a.name,
+                    ty: Some(&a.ty),
+                    text: comment.param(&a.name).unwrap_or(""),
+                });
+            }
+        }
+        for (name, text) in comment.params() {
+            if method.map(|m| m.args.iter().any(|a| a.name == name)).unwrap_or(false) {
+                continue;
+            }
+            params.push(Param { name, ty: None, text });
+        }
+
+        let ret = method.and_then(|m| m.ret.as_ref()).filter(|r| r.name != "void");
+        let returns = if ret.is_some() || comment.returns().is_some() {
+            Some(Returns {
+                ty: ret,
+                text: comment.returns().unwrap_or(""),
+            })
+        } else {
+            None
+        };
+
+        let mut throws = comment.throws()
+            .map(|(reference, text)| Throws::Documented { reference, text })
+            .collect::<Vec<Throws>>();
+        if let Some(m) = method {
+            for t in &m.throws {
+                if !comment.throws().any(|(ty, _)| ty == t.simple_name() || ty == t.name) {
+                    throws.push(Throws::Undocumented(t));
+                }
+            }
+        }
+
+        let see = comment.tags.iter().filter_map(|t| match t {
+            BlockTag::See(text) if text.starts_with('"') || text.starts_with('<') => Some(See::Text(text)),
+            BlockTag::See(text) => {
+                let (reference, label) = split_reference(text);
+                Some(See::Reference { reference, label })
+            },
+            _ => None,
+        }).collect();
+
+        let trailing = comment.tags.iter().filter_map(|t| match t {
+            BlockTag::Since(text) => Some(Trailing::Since(text)),
+            BlockTag::Author(text) => Some(Trailing::Author(text)),
+            _ => None,
+        }).collect();
+
+        Tags {
+            deprecated: comment.deprecated(),
+            params,
+            returns,
+            throws,
+            see,
+            trailing,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fmt;
+
+use crate::diagnostic::line_col;
+
+use super::escape::Html;
+
+// A piece of a template
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    // `{{ name }}`
+    Var(String),
+}
+
+// A problem in a template, with where it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+// Some html with `{{ variable }}`s in it to fill in
+// Templates are written by whoever made the theme, so their html is trusted, but everything
+// that goes into them has to be Html already
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    // Parse a template, checking that it only uses the variables in `vars`
+    pub fn parse(src: &str, vars: &[&str]) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut rest = 0;
+
+        while let Some(found) = src[rest..].find("{{") {
+            let start = rest + found;
+            let (line, col) = line_col(src, start);
+
+            let end = match src[start..].find("}}") {
+                Some(e) => start + e,
+                None => return Err(TemplateError { line, col, message: String::from("`{{` is never closed") }),
+            };
+
+            let name = src[start + 2..end].trim();
+            if !vars.contains(&name) {
+                return Err(TemplateError {
+                    line,
+                    col,
+                    message: format!("unknown variable `{}`, expected one of {}", name, vars.join(", ")),
+                });
+            }
+
+            parts.push(Part::Text(src[rest..start].to_owned()));
+            parts.push(Part::Var(name.to_owned()));
+            rest = end + 2;
+        }
+        parts.push(Part::Text(src[rest..].to_owned()));
+
+        Ok(Template { parts })
+    }
+
+    // Fill in the variables
+    // Anything parse() allowed but that isn't given here is left empty
+    pub fn render(&self, vars: &[(&str, &Html)]) -> Html {
+        let mut out = String::new();
+        for p in &self.parts {
+            match p {
+                Part::Text(t) => out += t,
+                Part::Var(name) => {
+                    if let Some((_, v)) = vars.iter().find(|(n, _)| n == name) {
+                        out += v.as_str();
+                    }
+                },
+            }
+        }
+        Html::trusted(out)
+    }
+}
@@ -4,26 +4,188 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::{Deserialize, Deserializer};
+
+use super::template::Template;
+
 // Read the default CSS file into a static str to use for later
-pub static DEFAULT_THEME_CSS: &'static str = include_str!("html/default.css");
+pub static DEFAULT_THEME_CSS: &str = include_str!("html/default.css");
+// These only change the colors, so they go after the default
+pub static DARK_THEME_CSS: &str = include_str!("html/dark.css");
+pub static AUTO_THEME_CSS: &str = include_str!("html/auto.css");
+
+// The light/dark toggle for the auto theme
+pub static TOGGLE_JS: &str = include_str!("html/toggle.js");
+
+// The templates every theme starts from
+pub static DEFAULT_PAGE: &str = include_str!("html/page.html");
+pub static DEFAULT_CLASS: &str = include_str!("html/class.html");
+pub static DEFAULT_SIDEBAR: &str = include_str!("html/sidebar.html");
+
+// What each template can use, and the file it's read from in a theme directory
+pub static PAGE_VARS: &[&str] = &["title", "heading", "head", "root", "sidebar", "content", "theme"];
+pub static CLASS_VARS: &[&str] = &[
+    "root", "package", "package_path", "kind", "name", "description", "tags",
+    "modifiers", "components", "constants", "fields", "nested", "definition",
+];
+pub static SIDEBAR_VARS: &[&str] = &["root", "logo", "search", "packages"];
 
 // Enumeration of each theme
+// A theme that isn't built in is a path, to either a CSS file or a directory with any of
+// `style.css`, `page.html`, `class.html` and `sidebar.html` in it
+#[derive(Debug, Clone, PartialEq)]
 pub enum Theme {
     Default,
+    Dark,
+    // Light or dark to match the system, with a toggle to pick one
+    Auto,
+    Custom(PathBuf),
+}
+
+// Everything a theme is made of, ready to use
+#[derive(Debug, Clone)]
+pub struct Style {
+    pub name: String,
+    pub css: String,
+    // Extra html for the <head> of every page
+    pub head: String,
+    pub page: Template,
+    pub class: Template,
+    pub sidebar: Template,
+}
+
+// A theme that couldn't be loaded
+#[derive(Debug)]
+pub struct ThemeError {
+    pub path: PathBuf,
+    // Line and column, for mistakes in templates
+    pub pos: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pos {
+            Some((line, col)) => write!(f, "{}:{}:{}: {}", self.path.display(), line, col, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
 }
 
+impl std::error::Error for ThemeError {}
+
 impl Theme {
+    // Every built-in theme, in the order they're listed in --help
+    pub const ALL: &'static [Theme] = &[Theme::Default, Theme::Dark, Theme::Auto];
+
+    // Look a theme up by the name reported by name()
+    pub fn from_name(name: &str) -> Option<Self> {
+        Theme::ALL.iter().find(|t| t.name().eq_ignore_ascii_case(name)).cloned()
+    }
+
+    // A built-in theme by name, or else a path to one
+    pub fn parse(s: &str) -> Self {
+        Theme::from_name(s).unwrap_or_else(|| Theme::Custom(PathBuf::from(s)))
+    }
+
     // Read the css theme file corresponding to the theme
+    // Custom themes use the default until they're loaded
     pub fn get(&self) -> String {
         match self {
-            Theme::Default => DEFAULT_THEME_CSS.to_owned()
+            Theme::Default | Theme::Custom(_) => DEFAULT_THEME_CSS.to_owned(),
+            Theme::Dark => format!("{}\n{}", DEFAULT_THEME_CSS, DARK_THEME_CSS),
+            Theme::Auto => format!("{}\n{}", DEFAULT_THEME_CSS, AUTO_THEME_CSS),
+        }
+    }
+
+    // Anything the theme needs in the <head> of every page
+    pub fn head(&self) -> String {
+        match self {
+            Theme::Auto => format!("<script>\n{}</script>", TOGGLE_JS),
+            _ => String::new(),
         }
     }
 
     pub fn name(&self) -> &str {
         match &self {
             Theme::Default => "default",
-            // _ => "undefined",
+            Theme::Dark => "dark",
+            Theme::Auto => "auto",
+            Theme::Custom(path) => path.file_stem().and_then(|s| s.to_str()).unwrap_or("custom"),
+        }
+    }
+
+    // Read everything the theme needs, falling back to the default for anything a custom theme leaves out
+    pub fn load(&self) -> Result<Style, ThemeError> {
+        let builtin = |src: &str, vars: &[&str]| {
+            Template::parse(src, vars).expect("built-in templates are valid")
+        };
+        let mut style = Style {
+            name: self.name().to_owned(),
+            css: self.get(),
+            head: self.head(),
+            page: builtin(DEFAULT_PAGE, PAGE_VARS),
+            class: builtin(DEFAULT_CLASS, CLASS_VARS),
+            sidebar: builtin(DEFAULT_SIDEBAR, SIDEBAR_VARS),
+        };
+
+        let path = match self {
+            Theme::Custom(path) => path,
+            _ => return Ok(style),
+        };
+
+        if path.is_dir() {
+            if let Some(css) = read(&path.join("style.css"))? {
+                style.css = css;
+            }
+            for (file, vars, template) in [
+                ("page.html", PAGE_VARS, &mut style.page),
+                ("class.html", CLASS_VARS, &mut style.class),
+                ("sidebar.html", SIDEBAR_VARS, &mut style.sidebar),
+            ] {
+                let file = path.join(file);
+                if let Some(src) = read(&file)? {
+                    *template = Template::parse(&src, vars).map_err(|e| ThemeError {
+                        path: file.clone(),
+                        pos: Some((e.line, e.col)),
+                        message: e.message,
+                    })?;
+                }
+            }
+        } else {
+            style.css = read(path)?.ok_or_else(|| ThemeError {
+                path: path.clone(),
+                pos: None,
+                message: String::from("not a built-in theme, CSS file or theme directory"),
+            })?;
         }
+
+        Ok(style)
+    }
+}
+
+// Read one file of a theme, if it's there
+fn read(path: &Path) -> Result<Option<String>, ThemeError> {
+    match fs::read_to_string(path) {
+        Ok(s) => Ok(Some(s)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ThemeError {
+            path: path.to_owned(),
+            pos: None,
+            message: e.to_string(),
+        }),
+    }
+}
+
+// Themes are written by name, or as a path, in bach.toml
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(d)?;
+        Ok(Theme::parse(&name))
     }
 }
@@ -0,0 +1,459 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// GitHub-flavored Markdown, laid out for a repository's wiki
+// GitHub finds wiki pages by their file name wherever they are, so every page goes at the top
+// level and links leave the `.md` off, the same as links the wiki makes itself
+
+use std::collections::HashMap;
+use std::io;
+
+use crate::doc::comment::Inline;
+use crate::doc::resolve::{Index, Target};
+use crate::doc::{Comment, Definition, Doc, MethodDef, Package};
+
+use super::link::{display, member_id, Resolver};
+use super::commonmark;
+use super::tags::{See, Tags, Throws, Trailing};
+use super::{Model, OutputBackend, Page, Sink, KINDS};
+
+// The name of a package's page, like `frc.robot`
+pub fn package_page(p: &Package) -> String {
+    if p.name.is_empty() {
+        String::from("default-package")
+    } else {
+        p.name.clone()
+    }
+}
+
+// The name of a type's page, like `frc.robot.Outer.Inner`
+pub fn type_page(d: &Doc) -> String {
+    d.full_name()
+}
+
+/// Writes a page for each package and type, with a `Home.md` and `_Sidebar.md`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownBackend;
+
+impl OutputBackend for MarkdownBackend {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn generate(&self, model: &Model, sink: &mut dyn Sink) -> io::Result<()> {
+        for page in pages(&model.title, model.packages, &model.index) {
+            sink.write(&page.path, &page.content)?;
+        }
+        Ok(())
+    }
+}
+
+// Every page of the wiki, with the sidebar GitHub shows next to each of them
+pub fn pages<'a>(title: &str, packages: &'a [Package<'a>], index: &Index<'a>) -> Vec<Page> {
+    let mut pages = vec![
+        Page { path: String::from("Home.md"), content: home(title, packages, index) },
+        Page { path: String::from("_Sidebar.md"), content: sidebar(title, packages) },
+    ];
+    for p in packages {
+        pages.push(Page { path: format!("{}.md", package_page(p)), content: package(p, index) });
+        for t in p.all_types() {
+            pages.push(Page { path: format!("{}.md", type_page(t)), content: class(p, t, index) });
+        }
+    }
+    pages
+}
+
+// The front page, listing every package
+fn home(title: &str, packages: &[Package], index: &Index) -> String {
+    let linker = Linker::new(index, None);
+    let mut s = format!("# {}\n\n| Package | Description |\n| --- | --- |\n", escape(title));
+    for p in packages {
+        s += &format!("| [{}]({}) | {} |\n", code(p.display_name()), package_page(p), linker.cell(&p.comment().summary));
+    }
+    s
+}
+
+fn sidebar(title: &str, packages: &[Package]) -> String {
+    let mut s = format!("**[{}](Home)**\n\n", escape(title));
+    for p in packages {
+        s += &format!("- [{}]({})\n", code(p.display_name()), package_page(p));
+        for t in p.all_types() {
+            s += &format!("  - [{}]({})\n", code(&t.qualified_name()), type_page(t));
+        }
+    }
+    s
+}
+
+// A package's overview, with every type in it
+fn package<'a>(p: &'a Package<'a>, index: &Index<'a>) -> String {
+    let linker = Linker::new(index, None);
+    let mut s = format!("# Package {}\n\n", code(p.display_name()));
+    let description = linker.block(&p.comment().description.join("\n"));
+    if !description.is_empty() {
+        s += &format!("{}\n\n", description);
+    }
+
+    for (kind, heading, _) in KINDS {
+        let types = p.all_types().into_iter().filter(|t| t.def.kind() == *kind).collect::<Vec<&Doc>>();
+        if types.is_empty() {
+            continue;
+        }
+        s += &format!("## {}\n\n| Name | Description |\n| --- | --- |\n", heading);
+        for t in types {
+            s += &format!("| [{}]({}) | {} |\n", code(&t.qualified_name()), type_page(t), Linker::new(index, Some(t)).cell(&t.comment().summary));
+        }
+        s += "\n";
+    }
+    s
+}
+
+// The sections of members on a type's page, in order
+// Links to members need these to work out the anchors GitHub will give them
+fn sections<'a>(t: &'a Doc<'a>) -> Vec<(&'static str, Vec<&'a Doc<'a>>)> {
+    let c = match t.def.class() {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+    let of = |f: fn(&Definition) -> bool| c.fields.iter().filter(|m| f(&m.def)).collect::<Vec<&Doc>>();
+
+    let methods = if let Definition::Annotation(_) = t.def { "Elements" } else { "Methods" };
+    vec![
+        ("Constants", c.constants.iter().collect()),
+        ("Fields", of(|d| matches!(d, Definition::Field(_)))),
+        ("Constructors", of(|d| matches!(d, Definition::Method(m) if m.is_constructor()))),
+        (methods, of(|d| matches!(d, Definition::Method(m) if !m.is_constructor()))),
+    ].into_iter().filter(|(_, m)| !m.is_empty()).collect()
+}
+
+// What a type's page is headed with, like "Class Outer.Inner"
+fn title(t: &Doc) -> String {
+    format!("{} {}", t.def.kind(), t.qualified_name())
+}
+
+// What a member's section is headed with, which has the parameter types of methods so
+// overloads don't share one
+fn heading(m: &Doc) -> String {
+    match &m.def {
+        Definition::Method(_) => member_id(m).replace(',', ", "),
+        _ => m.name().to_owned(),
+    }
+}
+
+// The anchor GitHub gives a member's heading on its type's page
+fn anchor(t: &Doc, member: &Doc) -> String {
+    let mut slugs = Slugs::default();
+    slugs.next(&title(t));
+    if t.def.class().map(|c| !c.components.is_empty()).unwrap_or(false) {
+        slugs.next("Components");
+    }
+    for (section, members) in sections(t) {
+        slugs.next(section);
+        for m in members {
+            let slug = slugs.next(&heading(m));
+            if std::ptr::eq(m, member) {
+                return slug;
+            }
+        }
+    }
+    slug(&heading(member))
+}
+
+// A page for a single class, interface, enum, record or annotation type
+fn class<'a>(p: &'a Package<'a>, t: &'a Doc<'a>, index: &Index<'a>) -> String {
+    let linker = Linker::new(index, Some(t));
+    let comment = t.comment();
+    let kind = t.def.kind();
+
+    let mut s = format!("# {} {}\n\nPackage [{}]({})\n\n", kind, code(&t.qualified_name()), code(p.display_name()), package_page(p));
+    let description = linker.block(&comment.description.join("\n"));
+    if !description.is_empty() {
+        s += &format!("{}\n\n", description);
+    }
+    s += &tags(&comment, None, &linker);
+
+    let c = match t.def.class() {
+        Some(c) => c,
+        None => return s,
+    };
+    s += &format!("```java\n{}\n```\n\n", c.raw());
+
+    if !c.components.is_empty() {
+        s += "## Components\n\n| Name | Type |\n| --- | --- |\n";
+        for v in &c.components {
+            s += &format!("| {} | {} |\n", cell(&code(&v.name)), cell(&code(&v.ty.to_string())));
+        }
+        s += "\n";
+    }
+
+    for (section, members) in sections(t) {
+        s += &format!("## {}\n\n", section);
+        for m in members {
+            let linker = linker.with(m);
+            let comment = m.comment();
+            let definition = match &m.def {
+                Definition::Field(f) => f.raw.clone(),
+                Definition::Constant(k) => k.raw.clone(),
+                Definition::Method(d) => {
+                    let raw = d.raw.trim().trim_end_matches('{').trim_end();
+                    match &d.default {
+                        Some(v) => format!("{} default {}", raw, v),
+                        None => raw.to_owned(),
+                    }
+                },
+                _ => String::new(),
+            };
+
+            s += &format!("### {}\n\n```java\n{}\n```\n\n", code(&heading(m)), definition);
+            let description = linker.block(&comment.description.join("\n"));
+            if !description.is_empty() {
+                s += &format!("{}\n\n", description);
+            }
+            s += &tags(&comment, match &m.def {
+                Definition::Method(d) => Some(d),
+                _ => None,
+            }, &linker);
+        }
+    }
+
+    if !c.nested.is_empty() {
+        s += "## Nested Types\n\n";
+        for n in &c.nested {
+            s += &format!("- {} [{}]({})\n", n.def.kind(), code(&n.qualified_name()), type_page(n));
+        }
+        s += "\n";
+    }
+
+    s
+}
+
+// The block tags of a comment, in the same sections as format_tags! makes for html
+fn tags(comment: &Comment, method: Option<&MethodDef>, linker: &Linker) -> String {
+    let tags = Tags::new(comment, method);
+    let mut s = String::new();
+
+    if let Some(text) = tags.deprecated {
+        s += &format!("**Deprecated.** {}\n\n", linker.line(text));
+    }
+
+    let params = tags.params.iter()
+        .map(|p| match p.ty {
+            Some(ty) => format!("| {} | {} | {} |", cell(&code(p.name)), cell(&code(&ty.to_string())), linker.cell(p.text)),
+            None => format!("| {} | | {} |", cell(&code(p.name)), linker.cell(p.text)),
+        })
+        .collect::<Vec<String>>();
+    if !params.is_empty() {
+        s += &format!("**Parameters**\n\n| Name | Type | Description |\n| --- | --- | --- |\n{}\n\n", params.join("\n"));
+    }
+
+    if let Some(r) = &tags.returns {
+        let ty = r.ty.map(|t| cell(&code(&t.to_string()))).unwrap_or_default();
+        s += &format!("**Returns**\n\n| Type | Description |\n| --- | --- |\n| {} | {} |\n\n", ty, linker.cell(r.text));
+    }
+
+    let throws = tags.throws.iter()
+        .map(|t| match t {
+            Throws::Documented { reference, text } => format!("| {} | {} |", cell(&linker.link(reference, None, false)), linker.cell(text)),
+            Throws::Undocumented(ty) => format!("| {} | |", cell(&code(&ty.to_string()))),
+        })
+        .collect::<Vec<String>>();
+    if !throws.is_empty() {
+        s += &format!("**Throws**\n\n| Type | Description |\n| --- | --- |\n{}\n\n", throws.join("\n"));
+    }
+
+    let see = tags.see.iter()
+        .map(|t| match t {
+            See::Text(text) => format!("- {}", linker.line(text)),
+            See::Reference { reference, label } => format!("- {}", linker.link(reference, label.as_deref(), false)),
+        })
+        .collect::<Vec<String>>();
+    if !see.is_empty() {
+        s += &format!("**See Also**\n\n{}\n\n", see.join("\n"));
+    }
+
+    for t in &tags.trailing {
+        match t {
+            Trailing::Since(text) => s += &format!("**Since** {}\n\n", linker.line(text)),
+            Trailing::Author(text) => s += &format!("**Author** {}\n\n", linker.line(text)),
+        }
+    }
+    s
+}
+
+// Turns doc text into Markdown, with links to other pages of the wiki
+#[derive(Clone, Copy)]
+struct Linker<'g, 'a> {
+    resolver: Resolver<'g, 'a>,
+}
+
+impl<'g, 'a> Linker<'g, 'a> {
+    fn new(index: &'g Index<'a>, context: Option<&'a Doc<'a>>) -> Self {
+        Linker {
+            resolver: Resolver::new(index, context),
+        }
+    }
+
+    fn with(&self, this: &'a Doc<'a>) -> Self {
+        Linker {
+            resolver: self.resolver.with(this),
+        }
+    }
+
+    fn href(&self, target: &Target) -> String {
+        match target.member {
+            Some(m) => format!("{}#{}", type_page(target.ty), anchor(target.ty, m)),
+            None => type_page(target.ty),
+        }
+    }
+
+    // A whole description, which is Markdown already
+    fn block(&self, text: &str) -> String {
+        commonmark::rewrite(text, |i| self.tag(i)).trim().to_owned()
+    }
+
+    // Some text on one line, like the text of a block tag
+    fn line(&self, text: &str) -> String {
+        self.block(text).lines().map(|l| l.trim()).collect::<Vec<&str>>().join(" ")
+    }
+
+    // Some text in a table
+    fn cell(&self, text: &str) -> String {
+        cell(&self.line(text))
+    }
+
+    fn tag(&self, i: &Inline) -> String {
+        match i {
+            Inline::Text(t) => t.clone(),
+            Inline::Literal(t) => escape(t),
+            Inline::Code(c) => code(c),
+            Inline::Link { target, label, plain } => self.link(target, label.as_deref(), *plain),
+            Inline::Value(target) => self.value(target.as_deref()),
+            Inline::Other { text, .. } => escape(text),
+        }
+    }
+
+    // A link to a reference, or just its name if it doesn't go anywhere
+    fn link(&self, reference: &str, label: Option<&str>, plain: bool) -> String {
+        let label = match label {
+            Some(l) => self.line(l),
+            None if plain => escape(&display(reference)),
+            None => code(&display(reference)),
+        };
+
+        match self.resolver.link(reference) {
+            Some(t) => format!("[{}]({})", label, self.href(&t)),
+            None => label,
+        }
+    }
+
+    // The value of a field, linked to it
+    fn value(&self, reference: Option<&str>) -> String {
+        match self.resolver.value(reference) {
+            (Some(t), Some(v)) => format!("[{}]({})", code(&v), self.href(&t)),
+            _ => code(&reference.map(display).unwrap_or_default()),
+        }
+    }
+}
+
+// Keep some text from being read as Markdown or html
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if "\\`*_{}[]<>()#+-.!|~&".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// Some text as inline code, with enough backticks around it that any inside it don't end it
+fn code(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{}{}{}{}{}", fence, pad, text, pad, fence)
+}
+
+// Make something safe to put in a table cell, where `|` would end it, even in code
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+// The anchor GitHub makes from a heading's text: lowercase, with spaces turned into dashes
+// and any other punctuation dropped
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+// Anchors of the headings on one page, where GitHub adds `-1`, `-2` and so on to repeats
+#[derive(Default)]
+struct Slugs {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugs {
+    fn next(&mut self, text: &str) -> String {
+        let slug = slug(text);
+        let count = self.seen.entry(slug.clone()).or_insert(0);
+        let out = if *count == 0 { slug } else { format!("{}-{}", slug, count) };
+        *count += 1;
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::scan::scan_source;
+
+    #[test]
+    fn slugs_like_github() {
+        assert_eq!(slug("Class Outer.Inner"), "class-outerinner");
+        assert_eq!(slug("drive(double, double)"), "drivedouble-double");
+        assert_eq!(slug("of(List<? extends T>[])"), "oflist-extends-t");
+        assert_eq!(slug("max_speed"), "max_speed");
+        assert_eq!(slug("Größe"), "größe");
+
+        let mut slugs = Slugs::default();
+        assert_eq!(slugs.next("Fields"), "fields");
+        assert_eq!(slugs.next("fields()"), "fields-1");
+        assert_eq!(slugs.next("Fields!"), "fields-2");
+        assert_eq!(slugs.next("Methods"), "methods");
+    }
+
+    #[test]
+    fn member_anchors() {
+        let src = "/** Drive. */\n\
+                   public class Drive {\n\
+                   \x20   /** Fields. */\n\
+                   \x20   public int fields;\n\
+                   \x20   /** Drives. */\n\
+                   \x20   public void drive(double speed, double rotation) {}\n\
+                   \x20   /** Drives less. */\n\
+                   \x20   public void drive(double speed) {}\n\
+                   \x20   /** Lists them. */\n\
+                   \x20   public void fields() {}\n\
+                   \x20   /** Puts. */\n\
+                   \x20   public void put(Map<String, List<Integer>> values) {}\n\
+                   }\n";
+        let docs = scan_source(Path::new("Drive.java"), src).docs;
+        let t = &docs[0];
+        let anchors = t.def.class().unwrap().fields.iter().map(|m| anchor(t, m)).collect::<Vec<String>>();
+        // `fields` repeats the Fields heading, and `fields()` repeats both
+        assert_eq!(anchors, vec!["fields-1", "drivedouble-double", "drivedouble", "fields-2", "putmap"]);
+    }
+}
@@ -4,5 +4,24 @@
 //  file, You can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
+//! Bach, a documentation generator for Java, as a library.
+//!
+//! [`Project`] runs the whole thing: it finds the source files, pulls the doc comments and the
+//! declarations they're on out of them, groups them into packages and checks their links, then
+//! hands them to an [`OutputBackend`] for each format. The modules underneath are there for
+//! anything [`Project`] doesn't cover, like scanning a single file with [`scan::scan_source`]
+//! or adding a backend of your own.
+
 pub mod gen;
 pub mod doc;
+pub mod config;
+pub mod coverage;
+pub mod diagnostic;
+pub mod lint;
+pub mod scan;
+mod project;
+
+pub use config::Config;
+pub use diagnostic::{Diagnostic, Severity};
+pub use gen::{Format, OutputBackend, Theme};
+pub use project::{Documentation, Error, Project};
@@ -0,0 +1,340 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// Checks that doc comments agree with what they document
+// Only documented declarations are checked, since everything else has nothing to disagree with
+
+use std::collections::BTreeMap;
+
+use crate::config::Level;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::doc::{Comment, Definition, Doc, MethodDef, Package};
+
+// Every lint, how seriously it's taken unless bach.toml says otherwise, and what it's for
+pub static LINTS: &[(&str, Level, &str)] = &[
+    ("missing-param", Level::Warn, "a parameter of a documented method or constructor without a @param"),
+    ("unknown-param", Level::Warn, "a @param for something that isn't a parameter"),
+    ("missing-return", Level::Warn, "a documented method that returns something without a @return"),
+    ("missing-throws", Level::Warn, "an exception in a `throws` clause without a @throws"),
+];
+
+// The level of every lint, with the ones bach.toml sets
+// Names that aren't lints are an error, listing the ones that are
+// Config::load() already points at them in bach.toml, this is for configs built in code
+pub fn levels(configured: &BTreeMap<String, Level>) -> Result<BTreeMap<&'static str, Level>, String> {
+    if let Some(name) = configured.keys().find(|n| !is_lint(n)) {
+        return Err(unknown(name));
+    }
+    Ok(LINTS.iter().map(|(name, default, _)| (*name, configured.get(*name).copied().unwrap_or(*default))).collect())
+}
+
+pub fn is_lint(name: &str) -> bool {
+    LINTS.iter().any(|(l, _, _)| *l == name)
+}
+
+// What's wrong with a name under [lints] that isn't a lint
+pub fn unknown(name: &str) -> String {
+    format!("unknown lint `{}` in [lints], expected one of {}",
+            name, LINTS.iter().map(|(l, _, _)| *l).collect::<Vec<&str>>().join(", "))
+}
+
+// Run every lint that isn't allowed over some packages, in the order things are declared
+pub fn check(packages: &[Package], levels: &BTreeMap<&'static str, Level>) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        levels,
+        out: Vec::new(),
+    };
+    for p in packages {
+        for t in p.all_types() {
+            checker.ty(t);
+        }
+    }
+    checker.out
+}
+
+struct Checker<'l> {
+    levels: &'l BTreeMap<&'static str, Level>,
+    out: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    // `at` is the line, and the column and length if it's in the declaration
+    fn emit(&mut self, lint: &'static str, doc: &Doc, at: (usize, Option<(usize, usize)>), message: String, help: String) {
+        let severity = match self.levels.get(lint).copied().unwrap_or(Level::Warn) {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        let mut d = Diagnostic::new(severity, &doc.file, at.0, message);
+        d.code = Some(lint);
+        if let Some((col, len)) = at.1 {
+            d.column = Some(col);
+            d.length = len;
+        }
+        d.help = Some(help);
+        self.out.push(d);
+    }
+
+    // A type's own @params can only be for its type parameters or a record's components
+    fn ty(&mut self, t: &Doc) {
+        let c = match t.def.class() {
+            Some(c) => c,
+            None => return,
+        };
+
+        if t.is_documented() {
+            let mut names = c.type_params.iter().map(|p| format!("<{}>", p.name)).collect::<Vec<String>>();
+            names.extend(c.components.iter().map(|v| v.name.clone()));
+            self.unknown_params(t, &t.comment(), &names);
+        }
+
+        let annotation = matches!(t.def, Definition::Annotation(_));
+        for m in &c.fields {
+            match &m.def {
+                Definition::Method(d) if m.is_documented() => self.method(m, d, annotation),
+                _ => (),
+            }
+        }
+    }
+
+    fn method(&mut self, m: &Doc, d: &MethodDef, annotation: bool) {
+        let comment = m.comment();
+        // `{@inheritDoc}` brings the tags along with it
+        if m.tag.iter().any(|l| l.contains("{@inheritDoc}")) {
+            return;
+        }
+
+        let mut names = d.args.iter().map(|a| a.name.clone()).collect::<Vec<String>>();
+        names.extend(d.type_params.iter().map(|p| format!("<{}>", p.name)));
+        self.unknown_params(m, &comment, &names);
+
+        for a in &d.args {
+            if comment.param(&a.name).is_none() {
+                self.emit("missing-param", m, declaration(m, &a.name),
+                          format!("parameter `{}` of `{}` has no @param", a.name, d.name),
+                          format!("add `@param {} ...` to the comment", a.name));
+            }
+        }
+
+        let ret = d.ret.as_ref().filter(|r| r.name != "void");
+        if let Some(ret) = ret {
+            // Annotation elements are documented by what they are, not what they return
+            if !annotation && comment.returns().is_none() {
+                self.emit("missing-return", m, declaration(m, &d.name),
+                          format!("`{}` returns `{}` but has no @return", d.name, ret),
+                          String::from("add `@return ...` to the comment"));
+            }
+        }
+
+        for t in &d.throws {
+            if !comment.throws().any(|(ty, _)| ty == t.simple_name() || ty == t.name) {
+                self.emit("missing-throws", m, declaration(m, &t.name),
+                          format!("`{}` throws `{}` but has no @throws for it", d.name, t),
+                          format!("add `@throws {} ...` to the comment", t.simple_name()));
+            }
+        }
+    }
+
+    // Every @param that doesn't name one of `names`, with a guess at what it meant
+    fn unknown_params(&mut self, doc: &Doc, comment: &Comment, names: &[String]) {
+        for (name, _) in comment.params() {
+            if names.iter().any(|n| n == name) {
+                continue;
+            }
+            let undocumented = names.iter().filter(|n| comment.param(n).is_none());
+            let help = match closest(name, undocumented) {
+                Some(n) => format!("did you mean `{}`?", n),
+                None if names.is_empty() => String::from("there aren't any parameters here"),
+                None => format!("the parameters are {}", names.iter().map(|n| format!("`{}`", n)).collect::<Vec<String>>().join(", ")),
+            };
+            let line = tag_line(doc, name);
+            self.emit("unknown-param", doc, (line, None),
+                      format!("@param `{}` isn't a parameter of `{}`", name, doc.name()),
+                      help);
+        }
+    }
+}
+
+// Where `word` is in a declaration, with its length, or else where the declaration starts
+fn declaration(doc: &Doc, word: &str) -> (usize, Option<(usize, usize)>) {
+    let len = word.chars().count();
+    let raw = match &doc.def {
+        Definition::Method(m) => &m.raw,
+        Definition::Field(f) => &f.raw,
+        _ => return (doc.pos.0, Some((doc.pos.1, 1))),
+    };
+
+    // The first time it's there as a whole word
+    let found = raw.match_indices(word).find(|(i, _)| {
+        let before = raw[..*i].chars().next_back();
+        let after = raw[i + word.len()..].chars().next();
+        !before.map(is_ident).unwrap_or(false) && !after.map(is_ident).unwrap_or(false)
+    });
+    match found {
+        Some((i, _)) => {
+            let before = &raw[..i];
+            let lines = before.matches('\n').count();
+            let col = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0);
+            // The first line of the declaration starts where it does in the file, the rest start at column 1
+            if lines == 0 {
+                (doc.pos.0, Some((doc.pos.1 + col, len)))
+            } else {
+                (doc.pos.0 + lines, Some((col + 1, len)))
+            }
+        },
+        None => (doc.pos.0, Some((doc.pos.1, 1))),
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+// The line of the comment the @param for `name` is on
+fn tag_line(doc: &Doc, name: &str) -> usize {
+    let n = doc.tag.iter().position(|l| {
+        let mut words = l.split_whitespace();
+        words.next() == Some("@param") && words.next() == Some(name)
+    });
+    doc.line + n.unwrap_or(0)
+}
+
+// The name that's closest to `name`, if one is close enough to be a typo
+fn closest<'n>(name: &str, names: impl Iterator<Item = &'n String>) -> Option<&'n String> {
+    names
+        .map(|n| (distance(&name.to_lowercase(), &n.to_lowercase()), n))
+        .filter(|(d, n)| *d <= n.chars().count().div_ceil(3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| n)
+}
+
+// How many characters have to change to turn one string into the other
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(prev + if ca == b[j] { 0 } else { 1 });
+            prev = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::scan::scan_source;
+
+    fn lint(src: &str, configured: &[(&str, Level)]) -> Vec<Diagnostic> {
+        let docs = scan_source(Path::new("Drive.java"), src).docs;
+        let packages = Package::group(docs, Vec::new());
+        let configured = configured.iter().map(|(n, l)| (n.to_string(), *l)).collect();
+        check(&packages, &levels(&configured).unwrap())
+    }
+
+    // Each diagnostic as its lint, line, column and message
+    fn summary(diagnostics: &[Diagnostic]) -> Vec<(&str, usize, Option<usize>, &str)> {
+        diagnostics.iter().map(|d| (d.code.unwrap_or(""), d.line, d.column, d.message.as_str())).collect()
+    }
+
+    #[test]
+    fn missing_and_unknown_params() {
+        let src = "/** Drive. */\n\
+                   public class Drive {\n\
+                   \x20   /**\n\
+                   \x20    * Drives.\n\
+                   \x20    * @param speed how fast\n\
+                   \x20    * @param rotaton how much to turn\n\
+                   \x20    * @param other nothing\n\
+                   \x20    */\n\
+                   \x20   public void drive(double speed, double rotation) {}\n\
+                   }\n";
+        assert_eq!(summary(&lint(src, &[])), vec![
+            ("unknown-param", 6, None, "@param `rotaton` isn't a parameter of `drive`"),
+            ("unknown-param", 7, None, "@param `other` isn't a parameter of `drive`"),
+            ("missing-param", 9, Some(44), "parameter `rotation` of `drive` has no @param"),
+        ]);
+        let out = lint(src, &[]);
+        assert_eq!(out[0].help.as_deref(), Some("did you mean `rotation`?"));
+        assert_eq!(out[1].help.as_deref(), Some("the parameters are `speed`, `rotation`"));
+    }
+
+    #[test]
+    fn returns() {
+        let src = "/** Drive. */\n\
+                   public class Drive {\n\
+                   \x20   /**\n\
+                   \x20    * Makes one.\n\
+                   \x20    * @return a drive\n\
+                   \x20    */\n\
+                   \x20   public Drive() {}\n\
+                   \x20   /** Stops. */\n\
+                   \x20   public void stop() {}\n\
+                   \x20   /**\n\
+                   \x20    * Starts.\n\
+                   \x20    * @return nothing\n\
+                   \x20    */\n\
+                   \x20   public void start() {}\n\
+                   \x20   /** Speed. */\n\
+                   \x20   public double speed() { return 0; }\n\
+                   \x20   /**\n\
+                   \x20    * Ports.\n\
+                   \x20    * @return the ports\n\
+                   \x20    */\n\
+                   \x20   public int[] ports() { return null; }\n\
+                   }\n";
+        // Constructors and void methods don't need one, and having one anyway does no harm
+        assert_eq!(summary(&lint(src, &[])), vec![
+            ("missing-return", 16, Some(19), "`speed` returns `double` but has no @return"),
+        ]);
+    }
+
+    #[test]
+    fn throws() {
+        let src = "/** Drive. */\n\
+                   public class Drive {\n\
+                   \x20   /**\n\
+                   \x20    * Opens.\n\
+                   \x20    * @throws IOException when it can't\n\
+                   \x20    */\n\
+                   \x20   public void open() throws java.io.IOException, IllegalStateException {}\n\
+                   }\n";
+        assert_eq!(summary(&lint(src, &[])), vec![
+            ("missing-throws", 7, Some(52), "`open` throws `IllegalStateException` but has no @throws for it"),
+        ]);
+    }
+
+    #[test]
+    fn levels_can_be_changed() {
+        let src = "/** Drive. */\n\
+                   public class Drive {\n\
+                   \x20   /** Speed. */\n\
+                   \x20   public double speed(int port) { return 0; }\n\
+                   }\n";
+        let out = lint(src, &[("missing-param", Level::Deny), ("missing-return", Level::Allow)]);
+        assert_eq!(summary(&out), vec![("missing-param", 4, Some(29), "parameter `port` of `speed` has no @param")]);
+        assert_eq!(out[0].severity, Severity::Error);
+
+        let configured = vec![(String::from("missing-parm"), Level::Deny)].into_iter().collect();
+        assert!(levels(&configured).unwrap_err().starts_with("unknown lint `missing-parm` in [lints], expected one of missing-param,"));
+    }
+
+    #[test]
+    fn closest_names() {
+        let names = [String::from("rotation"), String::from("speed"), String::from("x")];
+        assert_eq!(closest("rotaton", names.iter()), Some(&names[0]));
+        assert_eq!(closest("Speed", names.iter()), Some(&names[1]));
+        assert_eq!(closest("y", names.iter()), Some(&names[2]));
+        assert_eq!(closest("heading", names.iter()), None);
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+}
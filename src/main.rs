@@ -4,210 +4,174 @@
 //  file, You can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
-mod doc;
-mod gen;
+mod cli;
 
 use std::path::Path;
-use std::fs::{self, File};
-use std::io::{prelude::*, BufReader};
+use std::sync::atomic::{AtomicU8, Ordering};
 
-use doc::{Doc, Definition};
-use gen::{Generator, Theme};
+use bach::{Config, Diagnostic, Error, Format, Project, Severity};
+use bach::gen::DirSink;
 
-use regex::Regex;
+use clap::Parser;
 use colored::*;
 
-// Find a specific type of documentation out of a Vec<Doc>
-#[macro_export]
-macro_rules! find {
-    ( $x:ident => classes ) => {
-        // TODO(@monarrk): Is it possible to use into_iter() here to we don't need to return a
-        // reference?
-        $x.iter().filter(|d| d.is_class()).collect::<Vec<&Doc>>()
-    };
+use cli::MessageFormat;
 
-    ( $x:ident => methods ) => {
-        $x.iter().filter(|d| d.is_method()).collect::<Vec<&Doc>>()
-    };
-}
-
-macro_rules! extract_pkg {
-    ( $x:expr ) => {
-        match &$x {
-            Some(s) => String::from(s.clone()),
-            None => String::from("Unknown"),
-        }
-    };
-}
-
-// Where to output
-static BACH_DIR: &str = "./bach";
-
-// Walk through every directory and scan files
-fn walk<'a>(p: &Path, pattern: Regex) -> Result<Vec<Doc<'a>>, Box<dyn std::error::Error>> {
-    let paths = fs::read_dir(p)?;
-
-    // TODO(@monarrk): Remove global muts?
-    let mut isdoc = false;
-    let mut comments: Vec<Doc> = Vec::new();
-    let mut idx = 0usize;
-
-    // init comments vec
-    //comments.push(Doc::new(String::new()));
-
-    'outer: for path in paths {
-        // shadow path to unwrap and convert to an actual Path
-        let path = path?.path();
-
-        if path.is_dir() {
-            // If we find a directory, walk that directory recursively and append the result to the
-            // comment Vec
-            comments.append(&mut walk(&path.clone(), pattern.clone())?);
-        } else {
-            logb!(format!("Scanning file {}", path.to_str().unwrap()));
-            let reader = BufReader::new(File::open(path)?);
-
-            let mut package = None;
-
-            for line in reader.lines() {
-                // Unwrap line safely
-                let line = match line {
-                    Ok(l) => l,
-                    Err(_) => continue 'outer,
-                };
-
-                if line.starts_with("package ") {
-                    package = Some(format!("{}", line.trim()
-                        .trim_start_matches("package ")
-                        .trim_end_matches(";")));
-                }
-
-                // is `line` a doc comment?
-                if pattern.is_match(line.as_str()) {
-                    if comments.len() <= idx {
-                        comments.push(Doc::new(extract_pkg!(package)));
-                    }
-                    comments[idx].push(line.clone());
-                    isdoc = true;
-                } else {
-                    // Are we currently documenting?
-                    if isdoc {
-                        // Derive a definition from the line, hoping it's a definition
-                        match Definition::derive(line.clone()) {
-                            // if we match, set that to the definition
-                            Some(d) => {
-                                match d {
-                                    c @ Definition::Class(_) => {
-                                        comments[idx].set_def(c);
-                                        idx += 1;
-                                        isdoc = false;
-                                    },
-
-                                    f @ Definition::Field(_) | f @ Definition::Method(_) => {
-                                        // hack
-                                        let mut stop = false;
-                                        comments = comments.clone().into_iter().rev().map(|mut i| {
-                                            if stop {
-                                                return i;
-                                            } else if i.is_class() {
-                                                comments[idx].set_def(f.clone());
-                                                i.push_field(comments[idx].clone());
-                                                isdoc = false;
-                                                idx += 1;
-                                                stop = true;
-                                                return i;
-                                            } else {
-                                                return i;
-                                            }
-                                        }).collect();
-                                    },
-
-                                    /*
-                                    m @ Definition::Method(_) => {
-                                        
-                                    },
-                                    */
-
-                                    Definition::None => {}
-                                }
-                            },
-                            // if not, just continue
-                            None => ()
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(comments.clone())
-}
+// How much logb! prints, set once from the command line in main()
+// 0 is quiet, 1 is normal and 2 is verbose
+static VERBOSITY: AtomicU8 = AtomicU8::new(1);
 
 // Logging macro for common logging patterns
+// Everything is silenced by --quiet, and the `v` pattern only prints with --verbose
 #[macro_export]
 macro_rules! logb {
     // Default pattern
     ( $s:expr ) => {
-        println!("{} {}...", "[bach]".cyan(), $s);
+        if VERBOSITY.load(Ordering::Relaxed) >= 1 {
+            println!("{} {}...", "[bach]".cyan(), $s);
+        }
     };
 
     // No trailing ...
     (n $s:expr ) => {
-        println!("{} {}", "[bach]".cyan(), $s);
+        if VERBOSITY.load(Ordering::Relaxed) >= 1 {
+            println!("{} {}", "[bach]".cyan(), $s);
+        }
     };
 
     // No trailing ... and green, for when something is done
     (d $s:expr ) => {
-        println!("{} {}", "[bach]".green(), $s);
+        if VERBOSITY.load(Ordering::Relaxed) >= 1 {
+            println!("{} {}", "[bach]".green(), $s);
+        }
+    };
+
+    // Only shown with --verbose
+    (v $s:expr ) => {
+        if VERBOSITY.load(Ordering::Relaxed) >= 2 {
+            println!("{} {}...", "[bach]".cyan(), $s);
+        }
     };
 }
 
-fn main() -> std::io::Result<()> {
-    // Create ./bach if it does not exist
-    if !Path::new(BACH_DIR).exists() {
-        logb!("Initializing bach directory");
-        std::fs::create_dir(BACH_DIR)?;
+fn main() {
+    let args = cli::Args::parse();
+    VERBOSITY.store(args.verbosity(), Ordering::Relaxed);
+
+    // Read bach.toml, either the one we were given or the one in the scan root
+    let loaded = match &args.config {
+        Some(path) => Config::load(path).map(Some),
+        None => Config::discover(args.roots.first().map(|r| r.as_path()).unwrap_or_else(|| Path::new("./"))),
+    };
+    let mut config = match loaded {
+        Ok(Some(c)) => {
+            logb!(v "Using bach.toml");
+            c
+        },
+        Ok(None) => Config::default(),
+        Err(e) => fail(Error::Config(e)),
+    };
+    args.apply(&mut config);
+
+    let project = Project::from_config(config);
+
+    if args.coverage {
+        coverage(&project);
+        return;
     }
 
-    // Match doc comments
-    let pattern: Regex = Regex::new(r"(?i)^\s*///.*").expect("Failed to compile doc comment regex");
-    let cwd = Path::new("./");
+    // Load the theme up front so a mistake in it doesn't waste a whole scan
+    let backends = project.backends().unwrap_or_else(|e| fail(e));
+
     logb!("Scanning files");
-    let docs = match walk(cwd, pattern) {
-        Ok(d) => d,
-        Err(e) => {
-            eprintln!("Failed to walk directory: {}", e);
-            std::process::exit(1);
-        }
-    };
+    let mut docs = project.scan_with(|path| logb!(v format!("Scanning file {}", path.display()))).unwrap_or_else(|e| fail(e));
+    if args.deny_warnings {
+        docs.deny_warnings();
+    }
+
+    // Errors in the source stop anything being written
+    report(&docs.diagnostics, args.message_format);
+    if docs.has_errors() {
+        std::process::exit(1);
+    }
 
     logb!("Generating docs");
-    // Get classes out of the docs
-    let classes = find!(docs => classes);
-    // TODO(@monarrk): There's no way this needs to be this long
-    let mut generator = Generator::new(docs[0].pkg.clone(), classes, Theme::Default);
-    let out = generator.generate();
-
-    // Create the output file
-    let mut file = match File::create(&format!("{}/index.html", BACH_DIR)) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Couldn't open file for writing: {}", e);
-            std::process::exit(1);
+    let model = docs.model();
+    let out_dir = project.out_dir();
+    let mut sink = DirSink::new(out_dir.clone());
+    for backend in &backends {
+        logb!(format!("Writing {}", backend.name()));
+        if let Err(e) = backend.generate(&model, &mut sink) {
+            fail(Error::Write(e));
         }
+    }
+
+    let formats = project.formats();
+    let first = match formats[0] {
+        Format::Html => "index.html",
+        Format::Json => "bach.json",
+        Format::Markdown => "Home.md",
     };
-    
-    // Write the output file
-    logb!("Writing");
-    match file.write_all(out.as_bytes()) {
-        Ok(_) => (),
-        Err(e) => {
-            eprintln!("Couldn't write to file: {}", e);
+    logb!(d format!("Done! Find your docs in {}!", out_dir.join(first).display()));
+}
+
+// Report how much is documented, and fail if it's less than the threshold
+fn coverage(project: &Project) {
+    logb!("Counting documentation");
+    let coverage = project.coverage_with(|path| logb!(v format!("Scanning file {}", path.display()))).unwrap_or_else(|e| fail(e));
+
+    if VERBOSITY.load(Ordering::Relaxed) >= 1 {
+        print!("\n{}\n", coverage.table());
+    }
+    // Everything that's missing a comment, with --verbose
+    if VERBOSITY.load(Ordering::Relaxed) >= 2 && !coverage.undocumented.is_empty() {
+        println!("Undocumented");
+        for u in &coverage.undocumented {
+            println!("{}:{}: {} `{}`", u.file, u.line, u.kind.to_lowercase(), u.name);
+        }
+        println!();
+    }
+
+    if let Err(e) = project.write_coverage(&coverage) {
+        fail(e);
+    }
+    logb!(n format!("Wrote the report to {}", project.coverage_report().display()));
+
+    let percent = coverage.total.percent;
+    match project.config().coverage.threshold {
+        Some(threshold) if !project.passes(&coverage) => {
+            eprintln!("{} {:.1}% of the public API is documented, below the threshold of {}%", "error:".red(), percent, threshold);
             std::process::exit(1);
+        },
+        _ => logb!(d format!("{:.1}% of the public API is documented", percent)),
+    }
+}
+
+// Print every diagnostic, then how many there were of each kind
+fn report(diagnostics: &[Diagnostic], format: MessageFormat) {
+    if format == MessageFormat::Json {
+        for d in diagnostics {
+            println!("{}", serde_json::to_string(d).expect("diagnostics are always valid JSON"));
         }
-    };
+        return;
+    }
 
-    logb!(d "Done! Find your file in bach/index.html!");
+    for d in diagnostics {
+        eprintln!("{}\n", d.render());
+    }
+    let count = |s| diagnostics.iter().filter(|d| d.severity == s).count();
+    let plural = |n| if n == 1 { "" } else { "s" };
+    let (warnings, errors) = (count(Severity::Warning), count(Severity::Error));
+    if warnings > 0 {
+        eprintln!("{} {} warning{} emitted", "warning:".yellow().bold(), warnings, plural(warnings));
+    }
+    if errors > 0 {
+        eprintln!("{} not writing any documentation due to {} error{}", "error:".red().bold(), errors, plural(errors));
+    }
+}
 
-    Ok(())
+// Print an error and exit, with 2 if it was something we were told and 1 otherwise
+fn fail(e: Error) -> ! {
+    eprintln!("{} {}", "error:".red(), e);
+    std::process::exit(if e.is_usage() { 2 } else { 1 });
 }
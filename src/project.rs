@@ -0,0 +1,409 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use rayon::prelude::*;
+
+use crate::config::{Config, ConfigError};
+use crate::coverage::Coverage;
+use crate::diagnostic::{self, Diagnostic, Severity};
+use crate::doc::resolve::Index;
+use crate::doc::Package;
+use crate::gen::{DirSink, Format, HtmlBackend, JsonBackend, MarkdownBackend, Model, OutputBackend, Sink, Theme, ThemeError};
+use crate::lint;
+use crate::scan::Scanner;
+
+/// A Java project to document, and everything about how to do it.
+///
+/// Start from [`Project::new`], or [`Project::discover`] to read a `bach.toml`, and change
+/// anything else with the builder methods. Then [`scan`](Project::scan) it and
+/// [`write`](Project::write) the result, or look through the [`Documentation`] yourself.
+///
+/// ```no_run
+/// use bach::{Format, Project};
+///
+/// let project = Project::new()
+///     .root("src/main/java")
+///     .title("Robot Code")
+///     .format(Format::Html)
+///     .format(Format::Json)
+///     .out("docs");
+///
+/// let docs = project.scan()?;
+/// for d in &docs.diagnostics {
+///     eprintln!("{}\n", d);
+/// }
+/// if !docs.has_errors() {
+///     project.write(&docs)?;
+/// }
+/// # Ok::<(), bach::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Project {
+    config: Config,
+}
+
+/// Everything found in a project, grouped by package, ready for a backend.
+#[derive(Debug)]
+pub struct Documentation {
+    pub title: String,
+    /// Every package, ordered by name, with its top level types.
+    pub packages: Vec<Package<'static>>,
+    /// Everything wrong with the source that didn't stop the scan, in the order it is in the
+    /// files: declarations that couldn't be parsed, references that don't resolve and whatever
+    /// the lints found, apart from the ones that are allowed.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Why a project couldn't be documented.
+#[derive(Debug)]
+pub enum Error {
+    Config(ConfigError),
+    Glob(globset::Error),
+    Theme(ThemeError),
+    NotADirectory(PathBuf),
+    /// A name under `[lints]` that isn't a lint.
+    UnknownLint(String),
+    Walk { root: PathBuf, error: ignore::Error },
+    Read { path: PathBuf, error: io::Error },
+    Write(io::Error),
+    /// Nothing in the project had a doc comment.
+    Empty,
+}
+
+impl Project {
+    /// A project with every setting at its default: the current directory, written as html
+    /// into `./bach`.
+    pub fn new() -> Self {
+        Project::default()
+    }
+
+    /// A project with the settings from a config file, or anything else that made a [`Config`].
+    pub fn from_config(config: Config) -> Self {
+        Project { config }
+    }
+
+    /// A project using the `bach.toml` in `dir` if there is one. `dir` is scanned unless the
+    /// config lists its own `roots`.
+    pub fn discover(dir: &Path) -> Result<Self, Error> {
+        match Config::discover(dir).map_err(Error::Config)? {
+            Some(config) if !config.roots.is_empty() => Ok(Project::from_config(config)),
+            Some(config) => Ok(Project::from_config(config).root(dir)),
+            None => Ok(Project::new().root(dir)),
+        }
+    }
+
+    /// Add a directory to scan. The first one also names the documentation if there's no title.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.config.roots.push(root.into());
+        self
+    }
+
+    /// Only scan files matching this glob, relative to their root. Defaults to `*.java`.
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.config.include.push(glob.into());
+        self
+    }
+
+    /// Never scan files matching this glob, relative to their root.
+    /// The first one replaces [`DEFAULT_EXCLUDE`](crate::scan::DEFAULT_EXCLUDE), the same as `exclude` in bach.toml.
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.config.exclude.get_or_insert_with(Vec::new).push(glob.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.config.title = Some(title.into());
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.config.theme = Some(theme);
+        self
+    }
+
+    /// The URL or path of the image at the top of the html sidebar.
+    pub fn logo(mut self, logo: impl Into<String>) -> Self {
+        self.config.logo = Some(logo.into());
+        self
+    }
+
+    /// Add a kind of output to write. Defaults to just html.
+    pub fn format(mut self, format: Format) -> Self {
+        self.config.formats.push(format);
+        self
+    }
+
+    /// The lowest percentage of the public API that [`coverage`](Project::coverage) accepts.
+    pub fn threshold(mut self, percent: f64) -> Self {
+        self.config.coverage.threshold = Some(percent);
+        self
+    }
+
+    /// Where [`write`](Project::write) puts everything. Defaults to `./bach`.
+    pub fn out(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.out = Some(dir.into());
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn roots(&self) -> Vec<PathBuf> {
+        if self.config.roots.is_empty() {
+            vec![PathBuf::from("./")]
+        } else {
+            self.config.roots.clone()
+        }
+    }
+
+    pub fn out_dir(&self) -> PathBuf {
+        self.config.out.clone().unwrap_or_else(|| PathBuf::from("./bach"))
+    }
+
+    /// Where [`write_coverage`](Project::write_coverage) puts its report.
+    /// Defaults to `coverage.json` in the output directory.
+    pub fn coverage_report(&self) -> PathBuf {
+        self.config.coverage.report.clone().unwrap_or_else(|| self.out_dir().join("coverage.json"))
+    }
+
+    pub fn formats(&self) -> Vec<Format> {
+        if self.config.formats.is_empty() {
+            vec![Format::Html]
+        } else {
+            self.config.formats.clone()
+        }
+    }
+
+    /// Find, parse and group every documented declaration in the project, and check its links.
+    pub fn scan(&self) -> Result<Documentation, Error> {
+        self.scan_with(|_| ())
+    }
+
+    /// The same as [`scan`](Project::scan), calling `progress` with each file as it's read.
+    /// Files are read on several threads at once, so they don't come in any particular order,
+    /// but `progress` is always called on the thread that called this.
+    ///
+    /// ```no_run
+    /// let mut files = Vec::new();
+    /// let docs = bach::Project::new().scan_with(|path| files.push(path.to_owned()))?;
+    /// println!("{} files, {} packages", files.len(), docs.packages.len());
+    /// # Ok::<(), bach::Error>(())
+    /// ```
+    pub fn scan_with(&self, progress: impl FnMut(&Path)) -> Result<Documentation, Error> {
+        self.collect(false, progress)
+    }
+
+    /// Count how much of the public API is documented, by package and by file.
+    /// Unlike [`scan`](Project::scan), this sees declarations without doc comments too.
+    pub fn coverage(&self) -> Result<Coverage, Error> {
+        self.coverage_with(|_| ())
+    }
+
+    /// The same as [`coverage`](Project::coverage), calling `progress` with each file as it's
+    /// read, like [`scan_with`](Project::scan_with).
+    pub fn coverage_with(&self, progress: impl FnMut(&Path)) -> Result<Coverage, Error> {
+        let docs = self.collect(true, progress)?;
+        Ok(Coverage::measure(&docs.packages))
+    }
+
+    /// Is the coverage at least the project's threshold? Always true without one.
+    pub fn passes(&self, coverage: &Coverage) -> bool {
+        coverage.passes(self.config.coverage.threshold.unwrap_or(0.0))
+    }
+
+    /// Write a coverage report as JSON to [`coverage_report`](Project::coverage_report).
+    pub fn write_coverage(&self, coverage: &Coverage) -> Result<(), Error> {
+        let path = self.coverage_report();
+        let json = serde_json::to_string_pretty(coverage).expect("the report is always valid JSON") + "\n";
+        let with_path = |e: io::Error| Error::Write(io::Error::new(e.kind(), format!("{}: {}", path.display(), e)));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(with_path)?;
+        }
+        fs::write(&path, json).map_err(with_path)
+    }
+
+    // Scan every file, keeping declarations without comments if `undocumented` is set
+    // Each file is scanned on its own, in parallel, and the results are put back together in the
+    // order the files were listed in, so the output doesn't depend on which thread finished first
+    fn collect(&self, undocumented: bool, mut progress: impl FnMut(&Path)) -> Result<Documentation, Error> {
+        let levels = lint::levels(&self.config.lints).map_err(Error::UnknownLint)?;
+        let roots = self.roots();
+        // Bail out early on roots that don't exist rather than failing halfway through the walk
+        if let Some(root) = roots.iter().find(|r| !r.is_dir()) {
+            return Err(Error::NotADirectory(root.clone()));
+        }
+        let mut scanner = Scanner::new(&self.config.include, self.config.exclude.as_deref()).map_err(Error::Glob)?;
+        scanner.set_undocumented(undocumented);
+
+        let mut files = Vec::new();
+        for root in &roots {
+            files.append(&mut scanner.files(root).map_err(|error| Error::Walk { root: root.clone(), error })?);
+        }
+
+        // The workers send each path back here as they start on it, so `progress` doesn't have to
+        // be shared between threads
+        let scanned = thread::scope(|s| {
+            let (sender, receiver) = mpsc::channel();
+            let (files, scanner) = (&files, &scanner);
+            let workers = s.spawn(move || {
+                files
+                    .par_iter()
+                    .map(|path| {
+                        // Only fails if `progress` panicked, which is dealt with on the other side
+                        let _ = sender.send(path.as_path());
+                        scanner.scan(path)
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for path in receiver {
+                progress(path);
+            }
+            workers.join().unwrap_or_else(|e| panic::resume_unwind(e))
+        });
+
+        let mut docs = Vec::new();
+        let mut overviews = Vec::new();
+        let mut diagnostics = Vec::new();
+        // The first file that couldn't be read is the one reported, whichever thread got to it
+        for (path, scanned) in files.iter().zip(scanned) {
+            let mut scanned = scanned.map_err(|error| Error::Read { path: path.clone(), error })?;
+            diagnostics.extend(scanned.errors.iter().map(|e| Diagnostic::parse(path, e)));
+            docs.append(&mut scanned.docs);
+            if !scanned.overview.is_empty() {
+                overviews.push((scanned.package.unwrap_or_default(), scanned.overview));
+            }
+        }
+
+        if docs.is_empty() && overviews.is_empty() {
+            return Err(Error::Empty);
+        }
+        let packages = Package::group(docs, overviews);
+        diagnostics.extend(Index::new(&packages).unresolved().iter().map(Diagnostic::unresolved));
+        // Everything undocumented would have nothing to check anyway
+        if !undocumented {
+            diagnostics.append(&mut lint::check(&packages, &levels));
+        }
+        diagnostic::sort(&mut diagnostics);
+        diagnostic::add_snippets(&mut diagnostics);
+
+        Ok(Documentation {
+            // Name the docs after the project's directory unless we're told otherwise
+            title: self.config.title.clone().unwrap_or_else(|| default_title(&roots[0])),
+            packages,
+            diagnostics,
+        })
+    }
+
+    /// A backend for each of the project's formats, with its theme loaded.
+    /// Worth calling before [`scan`](Project::scan) so a broken theme doesn't waste a whole scan.
+    pub fn backends(&self) -> Result<Vec<Box<dyn OutputBackend>>, Error> {
+        let mut backends: Vec<Box<dyn OutputBackend>> = Vec::new();
+        for format in self.formats() {
+            backends.push(match format {
+                Format::Html => {
+                    let style = self.config.theme.clone().unwrap_or(Theme::Default).load().map_err(Error::Theme)?;
+                    let mut html = HtmlBackend::new(style);
+                    if let Some(logo) = &self.config.logo {
+                        html.set_logo(logo.clone());
+                    }
+                    Box::new(html)
+                },
+                Format::Json => Box::new(JsonBackend),
+                Format::Markdown => Box::new(MarkdownBackend),
+            });
+        }
+        Ok(backends)
+    }
+
+    /// Write the documentation in every format into the output directory.
+    pub fn write(&self, docs: &Documentation) -> Result<(), Error> {
+        self.write_to(docs, &mut DirSink::new(self.out_dir()))
+    }
+
+    /// Write the documentation in every format somewhere else, like into memory with a `Vec<Page>`.
+    pub fn write_to(&self, docs: &Documentation, sink: &mut dyn Sink) -> Result<(), Error> {
+        let model = docs.model();
+        for backend in self.backends()? {
+            backend.generate(&model, sink).map_err(Error::Write)?;
+        }
+        Ok(())
+    }
+}
+
+impl Documentation {
+    /// What backends are given, with an index for resolving references.
+    pub fn model(&self) -> Model<'_> {
+        Model::new(self.title.clone(), &self.packages)
+    }
+
+    /// Did anything deserve an error, like a lint set to `deny`? The binary won't write any
+    /// documentation if so.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    /// Make every warning an error, for `--deny-warnings`.
+    pub fn deny_warnings(&mut self) {
+        for d in &mut self.diagnostics {
+            d.severity = Severity::Error;
+        }
+    }
+}
+
+// The name of the directory being documented, for when there's no title
+fn default_title(root: &Path) -> String {
+    root.canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| String::from("Documentation"))
+}
+
+impl Error {
+    /// Was this caused by something bach was told, like a bad glob or theme, rather than by
+    /// something going wrong while it ran? The binary exits with 2 for these, and 1 otherwise.
+    pub fn is_usage(&self) -> bool {
+        matches!(self, Error::Config(_) | Error::Glob(_) | Error::Theme(_) | Error::NotADirectory(_) | Error::UnknownLint(_))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(e) => write!(f, "{}", e),
+            Error::Glob(e) => write!(f, "bad include/exclude glob: {}", e),
+            Error::Theme(e) => write!(f, "bad theme: {}", e),
+            Error::NotADirectory(root) => write!(f, "{} is not a directory", root.display()),
+            Error::UnknownLint(message) => write!(f, "{}", message),
+            Error::Walk { root, error } => write!(f, "failed to walk directory {}: {}", root.display(), error),
+            Error::Read { path, error } => write!(f, "failed to read {}: {}", path.display(), error),
+            Error::Write(e) => write!(f, "couldn't write {}", e),
+            Error::Empty => write!(f, "no documentation comments found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Config(e) => Some(e),
+            Error::Glob(e) => Some(e),
+            Error::Theme(e) => Some(e),
+            Error::Walk { error, .. } => Some(error),
+            Error::Read { error, .. } => Some(error),
+            Error::Write(e) => Some(e),
+            Error::NotADirectory(_) | Error::UnknownLint(_) | Error::Empty => None,
+        }
+    }
+}
@@ -0,0 +1,758 @@
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use crate::doc::lexer::{self, Token, TokenKind};
+use crate::doc::{parser, Doc, Definition, FieldDef, ParseError};
+
+/// Files scanned when the project doesn't say otherwise.
+pub static DEFAULT_INCLUDE: &[&str] = &["*.java"];
+
+/// Build output that isn't worth scanning, excluded unless the project gives its own excludes.
+pub static DEFAULT_EXCLUDE: &[&str] = &["**/build", "**/target"];
+
+/// The file whose `package` comment is the package's overview.
+pub static PACKAGE_INFO: &str = "package-info.java";
+
+/// Extra ignore file, using .gitignore syntax, for things that are tracked but shouldn't be documented.
+pub static IGNORE_FILE: &str = ".bachignore";
+
+/// Everything that came out of scanning one file.
+#[derive(Default)]
+pub struct Scanned<'a> {
+    /// Top level types, with everything inside them attached.
+    pub docs: Vec<Doc<'a>>,
+    /// The package the file is in.
+    pub package: Option<String>,
+    /// The comment on the `package` line, if this is a `package-info.java` that has one.
+    pub overview: Vec<String>,
+    /// Documented declarations that couldn't be made sense of, with lines counted from the top of
+    /// the file.
+    pub errors: Vec<ParseError>,
+}
+
+/// Finds the files to document and pulls the documentation out of them.
+pub struct Scanner {
+    include: GlobSet,
+    exclude: GlobSet,
+    // Keep declarations without doc comments too, for counting what isn't documented
+    undocumented: bool,
+}
+
+impl Scanner {
+    /// Make a scanner from include and exclude globs, matched against paths relative to each root.
+    /// An empty include list means [`DEFAULT_INCLUDE`], and no exclude list means [`DEFAULT_EXCLUDE`].
+    pub fn new(include: &[String], exclude: Option<&[String]>) -> Result<Self, globset::Error> {
+        let include = if include.is_empty() {
+            globs(DEFAULT_INCLUDE.iter().copied())?
+        } else {
+            globs(include.iter().map(|s| s.as_str()))?
+        };
+        let exclude = match exclude {
+            Some(exclude) => globs(exclude.iter().map(|s| s.as_str()))?,
+            None => globs(DEFAULT_EXCLUDE.iter().copied())?,
+        };
+
+        Ok(Scanner {
+            include,
+            exclude,
+            undocumented: false,
+        })
+    }
+
+    /// Keep every declaration, not just the documented ones.
+    pub fn set_undocumented(&mut self, keep: bool) {
+        self.undocumented = keep;
+    }
+
+    /// List every file under `root` that should be scanned, in a stable order.
+    /// Hidden files, anything in `.gitignore` or `.bachignore` and anything excluded are skipped.
+    pub fn files(&self, root: &Path) -> Result<Vec<PathBuf>, ignore::Error> {
+        let exclude = self.exclude.clone();
+        let base = root.to_owned();
+
+        let walker = WalkBuilder::new(root)
+            .add_custom_ignore_filename(IGNORE_FILE)
+            // Respect .gitignore even when the project isn't a git checkout (e.g. a downloaded zip)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            // Don't even descend into excluded directories
+            .filter_entry(move |e| !exclude.is_match(relative(&base, e.path())))
+            .build();
+
+        let mut files = Vec::new();
+        for entry in walker {
+            let entry = entry?;
+            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+
+            let rel = relative(root, entry.path());
+            if self.include.is_match(rel) && !self.exclude.is_match(rel) {
+                files.push(entry.into_path());
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Scan a single file for documentation.
+    /// Files that aren't valid UTF-8 are assumed to be binary, and come back empty.
+    pub fn scan<'a>(&self, path: &Path) -> io::Result<Scanned<'a>> {
+        match fs::read_to_string(path) {
+            Ok(src) => Ok(scan(path, &src, self.undocumented)),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(Scanned::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Pull the documentation out of the source of one file, named `path` in what comes back.
+/// The top level types come back with everything inside them attached.
+pub fn scan_source<'a>(path: &Path, src: &str) -> Scanned<'a> {
+    scan(path, src, false)
+}
+
+// The same as scan_source(), keeping declarations without comments if `undocumented` is set
+fn scan<'a>(path: &Path, src: &str, undocumented: bool) -> Scanned<'a> {
+    let tokens = match lexer::lex_file(src) {
+        Ok(t) => t,
+        Err(e) => return Scanned { errors: vec![e], ..Scanned::default() },
+    };
+
+    let mut walker = Walker {
+        src,
+        path,
+        tokens: &tokens,
+        undocumented,
+        line: 0,
+        pos: 0,
+        package: None,
+        imports: Vec::new(),
+        overview: Vec::new(),
+        docs: Vec::new(),
+        errors: Vec::new(),
+    };
+    walker.body(None, &[], false);
+
+    for d in &mut walker.docs {
+        d.imports = walker.imports.clone();
+    }
+    Scanned {
+        docs: walker.docs,
+        package: walker.package,
+        overview: walker.overview,
+        errors: walker.errors,
+    }
+}
+
+// Walks over the tokens of a file one declaration at a time, keeping track of which type it's in
+struct Walker<'s, 'a> {
+    src: &'s str,
+    path: &'s Path,
+    tokens: &'s [Token],
+    // Keep declarations that don't have a comment
+    undocumented: bool,
+    pos: usize,
+    // The line the last comments() started on
+    line: usize,
+    package: Option<String>,
+    imports: Vec<String>,
+    overview: Vec<String>,
+    // The top level types
+    docs: Vec<Doc<'a>>,
+    errors: Vec<ParseError>,
+}
+
+impl<'s, 'a> Walker<'s, 'a> {
+    fn peek(&self) -> Option<&'s Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn at(&self, p: &str) -> bool {
+        self.peek().map(|t| t.is(p)).unwrap_or(false)
+    }
+
+    // Read declarations until the `}` that closes `owner`, or the end of the file if there isn't one
+    // `scope` is the names of the types we're inside, and `constants` is whether we're at the
+    // start of an enum's body
+    fn body(&mut self, mut owner: Option<&mut Doc<'a>>, scope: &[String], mut constants: bool) {
+        loop {
+            let tag = self.comments();
+
+            let t = match self.peek() {
+                Some(t) => t,
+                None => return,
+            };
+
+            if t.is("}") {
+                self.pos += 1;
+                if owner.is_some() {
+                    return;
+                }
+                // A stray `}` at the top level, just carry on
+                continue;
+            }
+
+            if owner.is_none() && (t.is_word("package") || t.is_word("import")) {
+                self.statement(tag);
+                continue;
+            }
+
+            if constants {
+                constants = self.constant(owner.as_deref_mut(), tag, scope);
+                continue;
+            }
+
+            if t.is(";") {
+                self.pos += 1;
+                continue;
+            }
+
+            self.member(owner.as_deref_mut(), tag, scope);
+        }
+    }
+
+    // Any documentation comments in a row, as lines of text
+    fn comments(&mut self) -> Vec<String> {
+        let mut tag = Vec::new();
+        self.line = self.peek().map(|t| t.line).unwrap_or(0);
+        while let Some(t) = self.peek().filter(|t| t.kind == TokenKind::Doc) {
+            self.pos += 1;
+            if t.text.starts_with("///") {
+                tag.push(strip_slashes(&t.text));
+            } else {
+                let mut lines = strip_javadoc(&t.text);
+                // The text starts on the next line after a lone `/**`, and the lines of the tag
+                // are counted from where the text starts
+                if tag.is_empty() && t.text.trim_start_matches("/**").lines().next().map(|l| l.trim().is_empty()).unwrap_or(false) {
+                    self.line += 1;
+                }
+                tag.append(&mut lines);
+            }
+        }
+        tag
+    }
+
+    // A `package` or `import` statement
+    // A comment on `package` documents the whole package, but only in package-info.java, since
+    // anywhere else it's more likely to be a license header copied into every file
+    fn statement(&mut self, tag: Vec<String>) {
+        let is_package = self.peek().map(|t| t.is_word("package")).unwrap_or(false);
+        self.pos += 1;
+        // `import static a.B.c;` imports a member of `a.B`
+        let is_static = !is_package && self.peek().map(|t| t.is_word("static")).unwrap_or(false);
+        if is_static {
+            self.pos += 1;
+        }
+
+        let mut name = String::new();
+        while let Some(t) = self.peek() {
+            self.pos += 1;
+            if t.is(";") {
+                break;
+            }
+            name += &t.text;
+        }
+
+        if is_package {
+            self.package = Some(name);
+            if self.path.file_name().map(|f| f == PACKAGE_INFO).unwrap_or(false) {
+                self.overview = tag;
+            }
+        } else if is_static {
+            // Only the type matters for links
+            if let Some((ty, _)) = name.rsplit_once('.') {
+                self.imports.push(ty.to_owned());
+            }
+        } else {
+            self.imports.push(name);
+        }
+    }
+
+    // A field, method, constructor or type, and its body if it has one
+    fn member(&mut self, owner: Option<&mut Doc<'a>>, tag: Vec<String>, scope: &[String]) {
+        // Find the `{`, `;` or `=` that ends the declaration, and the first `,` that isn't inside
+        // brackets or generics in case it's `int a, b;`
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut generics = 0usize;
+        let mut comma = None;
+        while let Some(t) = self.peek() {
+            if t.is("(") || t.is("[") {
+                depth += 1;
+            } else if t.is(")") || t.is("]") {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && t.is("<") {
+                generics += 1;
+            } else if depth == 0 && t.is(">") {
+                generics = generics.saturating_sub(1);
+            } else if depth == 0 && generics == 0 && t.is(",") {
+                comma = comma.or(Some(self.pos));
+            } else if depth == 0 && (t.is("{") || t.is(";") || t.is("=") || t.is("}")) {
+                break;
+            }
+            self.pos += 1;
+        }
+        let end = self.pos;
+
+        // A `static { ... }` or instance initializer block, which has nothing to document
+        if self.at("{") && self.tokens[start..end].iter().all(|t| t.is_word("static")) {
+            self.skip_rest();
+            return;
+        }
+
+        let documented = !tag.is_empty();
+        let keep = documented || self.undocumented;
+        let mut doc = self.doc(tag, scope);
+        doc.pos = self.tokens.get(start).map(|t| (t.line, t.col)).unwrap_or((self.line, 1));
+
+        let parse = |tokens| match owner.as_deref() {
+            Some(Doc { def: Definition::Record(_), .. }) => parser::parse_record_member(self.src, tokens),
+            _ => parser::parse_tokens(self.src, tokens),
+        };
+        // Only a field can declare more than one thing, anything else has its commas in a list
+        // like `throws A, B`
+        let parsed = match comma {
+            Some(c) if !self.at("{") => match parse(&self.tokens[start..c]) {
+                Ok(f @ Definition::Field(_)) => {
+                    self.pos = c;
+                    Ok(f)
+                },
+                _ => parse(&self.tokens[start..end]),
+            },
+            _ => parse(&self.tokens[start..end]),
+        };
+        match parsed {
+            Ok(def) if def.is_type() && self.at("{") => {
+                self.pos += 1;
+                let is_enum = matches!(def, Definition::Enum(_));
+                let mut inner = scope.to_vec();
+                inner.push(def.class().map(|c| c.get_name().to_owned()).unwrap_or_default());
+                doc.set_def(def);
+                self.body(Some(&mut doc), &inner, is_enum);
+
+                // Only keep types that have something documented in them, unless we keep everything
+                let keep = keep || doc.def.class().map(|c| c.has_docs()).unwrap_or(false);
+                match owner {
+                    Some(o) if keep => o.push_nested(doc),
+                    None if keep => self.docs.push(doc),
+                    _ => (),
+                }
+                return;
+            },
+
+            Ok(Definition::Field(mut f)) if keep => {
+                if self.at("=") {
+                    f.value = self.initializer();
+                }
+                let mut fields = vec![(doc.pos, f)];
+                while self.at(",") {
+                    self.pos += 1;
+                    match self.declarator(&fields[0].1) {
+                        Some(field) => fields.push(field),
+                        None => break,
+                    }
+                }
+                self.skip_rest();
+
+                // A member outside of any type can't be documented
+                if let Some(o) = owner {
+                    // The comment is on every field the declaration has
+                    for (pos, f) in fields {
+                        let mut doc = self.doc(doc.tag.clone(), scope);
+                        doc.pos = pos;
+                        doc.set_def(Definition::Field(f));
+                        o.push_field(doc);
+                    }
+                }
+                return;
+            },
+
+            Ok(m @ Definition::Method(_)) if keep => {
+                if let Some(o) = owner {
+                    doc.set_def(m);
+                    o.push_field(doc);
+                }
+            },
+
+            Err(e) if documented => self.errors.push(e),
+
+            _ => (),
+        }
+
+        self.skip_rest();
+    }
+
+    // A new Doc for a declaration in this file, inside the types in `scope`
+    fn doc(&self, tag: Vec<String>, scope: &[String]) -> Doc<'a> {
+        let mut doc = Doc::new(self.package.clone().unwrap_or_default());
+        doc.tag = tag;
+        doc.scope = scope.to_vec();
+        doc.file = self.path.to_owned();
+        doc.line = self.line;
+        doc
+    }
+
+    // Another field after the first in `int a = 1, b;`, with the same type and modifiers
+    fn declarator(&mut self, first: &FieldDef) -> Option<((usize, usize), FieldDef)> {
+        let name = self.peek().filter(|t| t.kind == TokenKind::Ident)?;
+        self.pos += 1;
+        let mut dims = 0;
+        while self.at("[") && self.tokens.get(self.pos + 1).map(|t| t.is("]")).unwrap_or(false) {
+            self.pos += 2;
+            dims += 1;
+        }
+
+        // The first field's `raw` ends with its name and any C style `[]`, which are swapped for
+        // this one's
+        let at = first.raw.rfind(first.name.as_str()).unwrap_or(first.raw.len());
+        let mut ty = first.ty.clone();
+        ty.dims = ty.dims - first.raw[at..].matches('[').count() + dims;
+        let mut field = FieldDef {
+            name: name.text.clone(),
+            ty,
+            value: None,
+            raw: format!("{}{}", &first.raw[..at], &self.src[name.start..self.tokens[self.pos - 1].end]),
+            ..first.clone()
+        };
+        if self.at("=") {
+            field.value = self.initializer();
+        }
+        Some(((name.line, name.col), field))
+    }
+
+    // Skip past a field's `= ...`, returning the source of the value
+    // It stops at the `,` or `;` after it, which the caller deals with
+    fn initializer(&mut self) -> Option<String> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(t) = self.peek() {
+            if t.is("<") {
+                // The commas in `new HashMap<K, V>()` don't end anything
+                if let Some(end) = self.generic_end() {
+                    self.pos = end;
+                    continue;
+                }
+            } else if t.is("(") || t.is("[") || t.is("{") {
+                depth += 1;
+            } else if t.is(")") || t.is("]") || t.is("}") {
+                // The `}` closing the type we're in, so leave it for body()
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && (t.is(",") || t.is(";")) {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        if start >= self.pos {
+            return None;
+        }
+        Some(self.src[self.tokens[start].start..self.tokens[self.pos - 1].end].to_owned())
+    }
+
+    // If the `<` we're at starts type arguments rather than being a less than, the token after
+    // the matching `>`
+    // Type arguments only have names, `.`, `,`, `?`, `&`, `[]`, annotations and more of themselves
+    // in them, so anything else means it was a comparison
+    fn generic_end(&self) -> Option<usize> {
+        let mut depth = 0usize;
+        for (i, t) in self.tokens.iter().enumerate().skip(self.pos) {
+            if t.is("<") {
+                depth += 1;
+            } else if t.is(">") {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            } else if !(t.kind == TokenKind::Ident || [".", ",", "?", "&", "[", "]", "@"].iter().any(|p| t.is(p))) {
+                return None;
+            }
+        }
+        None
+    }
+
+    // Skip a method body or the rest of a field, up to and including the end of the declaration
+    // A block ends at its `}`, but a field goes on to the `;`, past any anonymous class in it
+    fn skip_rest(&mut self) {
+        let block = self.at("{");
+        let mut depth = 0usize;
+        while let Some(t) = self.peek() {
+            if t.is("(") || t.is("[") || t.is("{") {
+                depth += 1;
+            } else if t.is(")") || t.is("]") || t.is("}") {
+                // The `}` closing the type we're in, so leave it for body()
+                if depth == 0 {
+                    return;
+                }
+                depth -= 1;
+                // The end of a method or initializer block
+                if depth == 0 && block && t.is("}") {
+                    self.pos += 1;
+                    return;
+                }
+            } else if depth == 0 && t.is(";") {
+                self.pos += 1;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
+    // One constant at the start of an enum body
+    // Returns whether there are more constants to come
+    fn constant(&mut self, owner: Option<&mut Doc<'a>>, tag: Vec<String>, scope: &[String]) -> bool {
+        // Find the `,`, `;` or `}` after it
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(t) = self.peek() {
+            if t.is("(") || t.is("[") || t.is("{") {
+                depth += 1;
+            } else if depth == 0 && (t.is(",") || t.is(";") || t.is("}")) {
+                break;
+            } else if t.is(")") || t.is("]") || t.is("}") {
+                depth = depth.saturating_sub(1);
+            }
+            self.pos += 1;
+        }
+        let end = self.pos;
+
+        // `;` and `}` both end the constants, but `}` ends the enum too so body() needs to see it
+        let more = match self.peek() {
+            Some(t) if t.is(",") => {
+                self.pos += 1;
+                true
+            },
+            Some(t) if t.is(";") => {
+                self.pos += 1;
+                false
+            },
+            _ => false,
+        };
+
+        // Nothing between the last `,` and the `;`
+        if start == end {
+            return more;
+        }
+
+        match parser::parse_constant(self.src, &self.tokens[start..end]) {
+            Ok(c) => {
+                let mut doc = self.doc(tag, scope);
+                doc.pos = (self.tokens[start].line, self.tokens[start].col);
+                doc.set_def(Definition::Constant(c));
+                if let Some(e) = owner.and_then(|o| o.def.class_mut()) {
+                    e.push_constant(doc);
+                }
+            },
+            Err(e) if !tag.is_empty() => self.errors.push(e),
+            Err(_) => (),
+        }
+
+        more
+    }
+}
+
+// Turn a `/** */` block into lines of text
+fn strip_javadoc(text: &str) -> Vec<String> {
+    let body = text.trim_start_matches("/**").trim_end_matches("*/");
+    let mut lines = body.lines().map(strip_star).collect::<Vec<String>>();
+
+    // Don't keep the blank lines from a lone `/**` or `*/`
+    if lines.first().map(|l| l.is_empty()).unwrap_or(false) {
+        lines.remove(0);
+    }
+    if lines.last().map(|l| l.is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+    lines
+}
+
+// Take the leading `*` off a line inside a /** */ block
+fn strip_star(line: &str) -> String {
+    let line = line.trim_start();
+    let line = line.strip_prefix('*').unwrap_or(line);
+    line.strip_prefix(' ').unwrap_or(line).trim_end().to_owned()
+}
+
+// Take the `///` off a doc comment line
+fn strip_slashes(line: &str) -> String {
+    let line = line.trim_start().trim_start_matches("///");
+    line.strip_prefix(' ').unwrap_or(line).trim_end().to_owned()
+}
+
+// Compile a list of globs into one set
+fn globs<'s>(patterns: impl Iterator<Item = &'s str>) -> Result<GlobSet, globset::Error> {
+    let mut set = GlobSetBuilder::new();
+    for p in patterns {
+        set.add(Glob::new(p)?);
+    }
+    set.build()
+}
+
+// `path` relative to `root`, or `path` itself if it isn't inside `root`
+fn relative<'p>(root: &Path, path: &'p Path) -> &'p Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documented_compact_constructors() {
+        let src = "/** A point. */\n\
+                   public record Point(int x, int y) {\n\
+                   \x20   /** Checks the point. */\n\
+                   \x20   public Point {\n\
+                   \x20       if (x < 0) throw new IllegalArgumentException();\n\
+                   \x20   }\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Point.java"), src);
+        assert!(scanned.errors.is_empty(), "{:?}", scanned.errors);
+
+        let point = &scanned.docs[0];
+        let fields = &point.def.class().unwrap().fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name(), "Point");
+        assert_eq!(fields[0].line, 3);
+    }
+
+    #[test]
+    fn nested_and_anonymous_types() {
+        let src = "package frc.robot;\n\
+                   /** Outer. */\n\
+                   public class Outer {\n\
+                   \x20   /** Inner. */\n\
+                   \x20   public static class Inner<T> {\n\
+                   \x20       /** Deepest. */\n\
+                   \x20       interface Deepest {}\n\
+                   \x20   }\n\
+                   \x20   /** Runs. */\n\
+                   \x20   public void run() {\n\
+                   \x20       Runnable r = new Runnable() {\n\
+                   \x20           /** Inside an anonymous class. */\n\
+                   \x20           public void run() {}\n\
+                   \x20       };\n\
+                   \x20   }\n\
+                   \x20   /** After. */\n\
+                   \x20   int after;\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Outer.java"), src);
+        assert!(scanned.errors.is_empty(), "{:?}", scanned.errors);
+        assert_eq!(scanned.docs.len(), 1);
+
+        let outer = scanned.docs[0].def.class().unwrap();
+        // Anything in a method body is skipped, so the anonymous class's method isn't a member
+        assert_eq!(outer.fields.iter().map(|f| f.name()).collect::<Vec<&str>>(), vec!["run", "after"]);
+
+        let inner = &outer.nested[0];
+        assert_eq!(inner.qualified_name(), "Outer.Inner");
+        assert_eq!(inner.full_name(), "frc.robot.Outer.Inner");
+        let deepest = &inner.def.class().unwrap().nested[0];
+        assert_eq!(deepest.def.kind(), "Interface");
+        assert_eq!(deepest.qualified_name(), "Outer.Inner.Deepest");
+    }
+
+    #[test]
+    fn malformed_declarations() {
+        let src = "/** Fine. */\n\
+                   public class Fine {\n\
+                   \x20   /** Broken. */\n\
+                   \x20   public void (int a) {}\n\
+                   \x20   /** Still found. */\n\
+                   \x20   public int ok() { return 1; }\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Fine.java"), src);
+        assert_eq!(scanned.errors.len(), 1);
+        assert_eq!((scanned.errors[0].line, scanned.errors[0].col), (4, 17));
+
+        let fields = &scanned.docs[0].def.class().unwrap().fields;
+        assert_eq!(fields.iter().map(|f| f.name()).collect::<Vec<&str>>(), vec!["ok"]);
+    }
+
+    #[test]
+    fn several_fields_in_one_declaration() {
+        let src = "/** Ports. */\n\
+                   public class Ports {\n\
+                   \x20   /** The motors. */\n\
+                   \x20   public static final int LEFT = 1, RIGHT = max(2, 3), spare[] = {4, 5}, none;\n\
+                   \x20   /** Lookup. */\n\
+                   \x20   Map<String, Integer> a = new HashMap<String, Integer>(), b;\n\
+                   \x20   /** Compared. */\n\
+                   \x20   boolean less = LEFT < RIGHT, more = LEFT > RIGHT;\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Ports.java"), src);
+        assert!(scanned.errors.is_empty(), "{:?}", scanned.errors);
+
+        let fields = scanned.docs[0].def.class().unwrap().fields.iter().map(|d| match &d.def {
+            Definition::Field(f) => (f.name.as_str(), f.ty.dims, f.value.as_deref(), f.raw.as_str(), d.pos),
+            _ => unreachable!(),
+        }).collect::<Vec<_>>();
+        assert_eq!(fields, vec![
+            ("LEFT", 0, Some("1"), "public static final int LEFT", (4, 5)),
+            ("RIGHT", 0, Some("max(2, 3)"), "public static final int RIGHT", (4, 39)),
+            ("spare", 1, Some("{4, 5}"), "public static final int spare[]", (4, 58)),
+            ("none", 0, None, "public static final int none", (4, 76)),
+            ("a", 0, Some("new HashMap<String, Integer>()"), "Map<String, Integer> a", (6, 5)),
+            ("b", 0, None, "Map<String, Integer> b", (6, 62)),
+            ("less", 0, Some("LEFT < RIGHT"), "boolean less", (8, 5)),
+            ("more", 0, Some("LEFT > RIGHT"), "boolean more", (8, 34)),
+        ]);
+        assert!(scanned.docs[0].def.class().unwrap().fields.iter().all(|d| d.tag.len() == 1));
+    }
+
+    #[test]
+    fn anonymous_classes_in_initializers() {
+        let src = "/** Holder. */\n\
+                   public class Holder {\n\
+                   \x20   /** A name. */\n\
+                   \x20   String name = new Object() { public String toString() { return \"x\"; } }.toString();\n\
+                   \x20   Object skipped = new Object() { }.hashCode();\n\
+                   \x20   /** After. */\n\
+                   \x20   int after;\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Holder.java"), src);
+        assert!(scanned.errors.is_empty(), "{:?}", scanned.errors);
+
+        let fields = &scanned.docs[0].def.class().unwrap().fields;
+        assert_eq!(fields.iter().map(|f| f.name()).collect::<Vec<&str>>(), vec!["name", "after"]);
+        match &fields[0].def {
+            Definition::Field(f) => assert_eq!(f.value.as_deref(), Some("new Object() { public String toString() { return \"x\"; } }.toString()")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn documented_initializer_blocks() {
+        let src = "/** Robot. */\n\
+                   public class Robot {\n\
+                   \x20   /** Sets up the ports. */\n\
+                   \x20   static {\n\
+                   \x20       PORTS = load();\n\
+                   \x20   }\n\
+                   \x20   /** Per instance. */\n\
+                   \x20   {\n\
+                   \x20       count++;\n\
+                   \x20   }\n\
+                   \x20   /** After. */\n\
+                   \x20   int after;\n\
+                   }\n";
+        let scanned = scan_source(Path::new("Robot.java"), src);
+        assert!(scanned.errors.is_empty(), "{:?}", scanned.errors);
+
+        let fields = &scanned.docs[0].def.class().unwrap().fields;
+        assert_eq!(fields.iter().map(|f| f.name()).collect::<Vec<&str>>(), vec!["after"]);
+    }
+}